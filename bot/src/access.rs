@@ -0,0 +1,236 @@
+use builders::locale::Locale;
+use twilight_model::guild::{PartialMember, Permissions};
+use twilight_model::id::marker::RoleMarker;
+use twilight_model::id::Id;
+
+use crate::config::RoleConfig;
+use crate::error::InteractionError;
+use crate::i18n;
+
+/// A role from the [role config](RoleConfig), referenced by its name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+pub(crate) enum ConfigRole {
+    DevforumMember,
+    DevforumRegular,
+    RobloxVerified,
+}
+
+impl ConfigRole {
+    /// Returns the ID of the role, or [`None`] if the role isn't configured.
+    pub(crate) fn resolve(self, roles: &RoleConfig) -> Option<Id<RoleMarker>> {
+        match self {
+            ConfigRole::DevforumMember => Some(roles.devforum_member),
+            ConfigRole::DevforumRegular => Some(roles.devforum_regular),
+            ConfigRole::RobloxVerified => roles.roblox_verified,
+        }
+    }
+}
+
+/// Requirements a member must meet to use an interaction,
+/// enforced by the dispatcher before the handler is executed.
+#[derive(Debug, Clone)]
+pub(crate) struct Access {
+    permissions: Permissions,
+    roles: Vec<ConfigRole>,
+}
+
+impl Access {
+    /// Create requirements that any member meets.
+    pub(crate) fn new() -> Self {
+        Self {
+            permissions: Permissions::empty(),
+            roles: Vec::new(),
+        }
+    }
+
+    /// Require the member to have the given permissions in the channel.
+    ///
+    /// Unlike the default member permissions of a command,
+    /// this can't be overridden by server admins.
+    pub(crate) fn permissions(mut self, permissions: Permissions) -> Self {
+        self.permissions |= permissions;
+        self
+    }
+
+    /// Require the member to have the given configured role.
+    ///
+    /// The requirement is ignored if the role isn't configured.
+    pub(crate) fn role(mut self, role: ConfigRole) -> Self {
+        self.roles.push(role);
+        self
+    }
+
+    /// Checks whether the member meets the requirements.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [permission error](InteractionError::Permission) describing
+    /// the first unmet requirement in the locale of the user.
+    pub(crate) fn check(
+        &self,
+        member: Option<&PartialMember>,
+        roles: &RoleConfig,
+        locale: &Locale,
+    ) -> Result<(), InteractionError> {
+        let role_ids: Vec<Id<RoleMarker>> = self
+            .roles
+            .iter()
+            .filter_map(|role| role.resolve(roles))
+            .collect();
+
+        if self.permissions.is_empty() && role_ids.is_empty() {
+            return Ok(());
+        }
+
+        let Some(member) = member else {
+            return Err(InteractionError::permission(i18n::message(
+                locale,
+                "access.guild_only",
+                &[],
+            )));
+        };
+
+        let missing = self.permissions - member.permissions.unwrap_or_else(Permissions::empty);
+        if !missing.is_empty() {
+            return Err(InteractionError::permission(i18n::message(
+                locale,
+                "access.missing_permissions",
+                &[("permissions", &permission_names(missing))],
+            )));
+        }
+
+        if let Some(role_id) = role_ids.iter().find(|id| !member.roles.contains(id)) {
+            return Err(InteractionError::permission(i18n::message(
+                locale,
+                "access.missing_role",
+                &[("role", role_id)],
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the names of the permissions as shown in Discord, such as `Manage Channels`.
+fn permission_names(permissions: Permissions) -> String {
+    permissions
+        .iter_names()
+        .map(|(name, _)| {
+            name.split('_')
+                .map(|word| word[..1].to_string() + &word[1..].to_lowercase())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_model::guild::MemberFlags;
+
+    use super::*;
+
+    fn roles() -> RoleConfig {
+        RoleConfig {
+            devforum_member: Id::new(1),
+            devforum_regular: Id::new(2),
+            roblox_verified: Some(Id::new(3)),
+            cooldown_bypass: Vec::new(),
+        }
+    }
+
+    fn member(permissions: Permissions, roles: Vec<Id<RoleMarker>>) -> PartialMember {
+        PartialMember {
+            avatar: None,
+            communication_disabled_until: None,
+            deaf: false,
+            flags: MemberFlags::empty(),
+            joined_at: None,
+            mute: false,
+            nick: None,
+            permissions: Some(permissions),
+            premium_since: None,
+            roles,
+            user: None,
+        }
+    }
+
+    #[test]
+    fn no_requirements() {
+        assert!(Access::new().check(None, &roles(), &i18n::FALLBACK).is_ok());
+    }
+
+    #[test]
+    fn permissions() {
+        let access = Access::new().permissions(Permissions::MANAGE_CHANNELS);
+        let allowed = member(
+            Permissions::MANAGE_CHANNELS | Permissions::SEND_MESSAGES,
+            vec![],
+        );
+        let denied = member(Permissions::SEND_MESSAGES, vec![]);
+
+        assert!(access
+            .check(Some(&allowed), &roles(), &i18n::FALLBACK)
+            .is_ok());
+        assert!(matches!(
+            access.check(Some(&denied), &roles(), &i18n::FALLBACK),
+            Err(InteractionError::Permission(_))
+        ));
+        assert!(access.check(None, &roles(), &i18n::FALLBACK).is_err());
+        assert_eq!(
+            permission_names(Permissions::MANAGE_CHANNELS | Permissions::MANAGE_ROLES),
+            "Manage Channels, Manage Roles"
+        );
+    }
+
+    #[test]
+    fn resolves_devforum_member() {
+        assert_eq!(
+            ConfigRole::DevforumMember.resolve(&roles()),
+            Some(Id::new(1))
+        );
+    }
+
+    #[test]
+    fn resolves_devforum_regular() {
+        assert_eq!(
+            ConfigRole::DevforumRegular.resolve(&roles()),
+            Some(Id::new(2))
+        );
+    }
+
+    #[test]
+    fn resolves_roblox_verified() {
+        assert_eq!(
+            ConfigRole::RobloxVerified.resolve(&roles()),
+            Some(Id::new(3))
+        );
+    }
+
+    #[test]
+    fn roles_requirement() {
+        let access = Access::new().role(ConfigRole::RobloxVerified);
+        let allowed = member(Permissions::empty(), vec![Id::new(3)]);
+        let denied = member(Permissions::empty(), vec![Id::new(1)]);
+
+        assert!(access
+            .check(Some(&allowed), &roles(), &i18n::FALLBACK)
+            .is_ok());
+        assert!(access
+            .check(Some(&denied), &roles(), &i18n::FALLBACK)
+            .is_err());
+    }
+
+    #[test]
+    fn unconfigured_role() {
+        let access = Access::new().role(ConfigRole::RobloxVerified);
+        let roles = RoleConfig {
+            roblox_verified: None,
+            ..roles()
+        };
+
+        assert!(access.check(None, &roles, &i18n::FALLBACK).is_ok());
+    }
+}
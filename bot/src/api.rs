@@ -0,0 +1,387 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use reqwest::header::{AUTHORIZATION, COOKIE};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_repr::Deserialize_repr;
+use twilight_model::id::marker::{GuildMarker, UserMarker};
+use twilight_model::id::Id;
+
+use crate::config::SecretsConfig;
+
+/// The base URLs of the APIs used by the bot.
+///
+/// Tests point these to a local stand-in server.
+#[derive(Debug, Clone)]
+pub(crate) struct ApiUrls {
+    /// The host of the Discord API (e.g. `localhost:8080`), served over plain HTTP.
+    ///
+    /// Discord is reached directly if not set.
+    pub(crate) discord: Option<String>,
+    pub(crate) rover: String,
+    pub(crate) roblox_users: String,
+    pub(crate) devforum: String,
+}
+
+impl Default for ApiUrls {
+    fn default() -> Self {
+        Self {
+            discord: None,
+            rover: "https://registry.rover.link".to_string(),
+            roblox_users: "https://users.roblox.com".to_string(),
+            devforum: "https://devforum.roblox.com".to_string(),
+        }
+    }
+}
+
+impl ApiUrls {
+    /// Returns the URLs of every API served by the stand-in server at the given base URL.
+    #[cfg(test)]
+    pub(crate) fn stand_in(base_url: &str) -> Self {
+        Self {
+            discord: Some(base_url.trim_start_matches("http://").to_string()),
+            rover: base_url.to_string(),
+            roblox_users: base_url.to_string(),
+            devforum: base_url.to_string(),
+        }
+    }
+
+    /// Creates the Discord HTTP client, pointing to the configured host if set.
+    pub(crate) fn discord_client(&self, token: String) -> twilight_http::Client {
+        let builder = twilight_http::Client::builder().token(token);
+        match &self.discord {
+            // Stand-in servers don't send ratelimit headers.
+            Some(host) => builder.proxy(host.clone(), true).ratelimiter(None),
+            None => builder,
+        }
+        .build()
+    }
+}
+
+/// The external APIs used to verify the DevForum rank of users.
+#[async_trait]
+pub(crate) trait ExternalApi: Send + Sync {
+    /// Fetches the Roblox ID using the Discord ID from the RoVer API,
+    /// or [`None`] if the user isn't linked to a Roblox account.
+    async fn fetch_rover_data(
+        &self,
+        guild_id: Id<GuildMarker>,
+        discord_id: Id<UserMarker>,
+    ) -> anyhow::Result<Option<RoVerAPIResponse>>;
+
+    /// Fetches the Roblox username using the Roblox ID from the Roblox API.
+    async fn fetch_roblox_data(&self, roblox_id: u64) -> anyhow::Result<RobloxAPIResponse>;
+
+    /// Fetches the DevForum profile using the Roblox username from the DevForum API.
+    async fn fetch_devforum_data(&self, roblox_username: &str) -> anyhow::Result<DevForumProfile>;
+}
+
+/// The DevForum profile of a Roblox user.
+pub(crate) enum DevForumProfile {
+    /// The profile, which the bot can see.
+    Visible(DevForumAPIResponse),
+    /// The profile is hidden from the bot.
+    Hidden,
+    /// The user doesn't have a DevForum account.
+    Missing,
+}
+
+/// Implements the [external APIs](ExternalApi) over HTTP.
+pub(crate) struct HttpApi {
+    request: reqwest::Client,
+    urls: ApiUrls,
+    rover_api_key: Option<String>,
+    devforum_cookie: Option<String>,
+}
+
+impl HttpApi {
+    /// Creates the client with the credentials from the config.
+    pub(crate) fn new(urls: ApiUrls, secrets: &SecretsConfig) -> Self {
+        Self {
+            request: reqwest::Client::new(),
+            urls,
+            rover_api_key: secrets
+                .rover_api_key
+                .as_ref()
+                .map(|key| key.expose().to_string()),
+            devforum_cookie: secrets
+                .devforum_cookie
+                .as_ref()
+                .map(|cookie| cookie.expose().to_string()),
+        }
+    }
+
+    /// Constructs the RoVer API endpoint URL.
+    fn rover_endpoint(&self, guild_id: Id<GuildMarker>, discord_id: Id<UserMarker>) -> String {
+        format!(
+            "{}/api/guilds/{guild_id}/discord-to-roblox/{discord_id}",
+            self.urls.rover
+        )
+    }
+
+    /// Constructs the Roblox API endpoint URL.
+    fn roblox_endpoint(&self, roblox_id: u64) -> String {
+        format!("{}/v1/users/{roblox_id}", self.urls.roblox_users)
+    }
+
+    /// Constructs the DevForum API endpoint URL.
+    fn devforum_endpoint(&self, roblox_username: &str) -> String {
+        format!("{}/u/{roblox_username}.json", self.urls.devforum)
+    }
+}
+
+#[async_trait]
+impl ExternalApi for HttpApi {
+    async fn fetch_rover_data(
+        &self,
+        guild_id: Id<GuildMarker>,
+        discord_id: Id<UserMarker>,
+    ) -> anyhow::Result<Option<RoVerAPIResponse>> {
+        let rover_api_key = self
+            .rover_api_key
+            .as_ref()
+            .context("missing secrets.rover_api_key")?;
+        let res = crate::metrics::external(
+            "rover",
+            self.request
+                .get(self.rover_endpoint(guild_id, discord_id))
+                .header(AUTHORIZATION, format!("Bearer {rover_api_key}")),
+        )
+        .await
+        .context("fetch rover data")?;
+
+        if res.status() == StatusCode::NOT_FOUND {
+            Ok(None)
+        } else if res.status().is_success() {
+            res.json::<RoVerAPIResponse>()
+                .await
+                .map(Some)
+                .context("parse rover data")
+        } else {
+            Err(anyhow::anyhow!(
+                "Failed to fetch RoVer data for discord_id={discord_id} in guild_id={guild_id}, received status: {}",
+                res.status()
+            ))
+        }
+    }
+
+    async fn fetch_roblox_data(&self, roblox_id: u64) -> anyhow::Result<RobloxAPIResponse> {
+        let res =
+            crate::metrics::external("roblox", self.request.get(self.roblox_endpoint(roblox_id)))
+                .await
+                .context("fetch roblox data")?;
+
+        // Check if the response was successful and parse the JSON data.
+        if res.status().is_success() {
+            res.json::<RobloxAPIResponse>()
+                .await
+                .context("parse roblox data")
+        } else {
+            Err(anyhow::anyhow!(
+                "Failed to fetch Roblox data for roblox_id={roblox_id}, received status: {}",
+                res.status()
+            ))
+        }
+    }
+
+    async fn fetch_devforum_data(&self, roblox_username: &str) -> anyhow::Result<DevForumProfile> {
+        let endpoint = self.devforum_endpoint(roblox_username);
+
+        // Attempt request without the cookie first
+        let res = crate::metrics::external("devforum", self.request.get(&endpoint)).await?;
+        match res.status() {
+            status if status.is_success() => {
+                if let Ok(data) = res.json::<DevForumAPIResponse>().await {
+                    return Ok(DevForumProfile::Visible(data));
+                }
+            },
+            StatusCode::NOT_FOUND => return Ok(DevForumProfile::Missing),
+            StatusCode::FORBIDDEN => {},
+            status => anyhow::bail!(
+                "Failed to fetch DevForum data for roblox_username={roblox_username} without cookie, received status: {status}"
+            ),
+        }
+
+        // The profile is hidden to visitors, which the cookie may be able to see
+        let Some(devforum_cookie) = &self.devforum_cookie else {
+            return Ok(DevForumProfile::Hidden);
+        };
+
+        // If the request fails, try again with the cookie
+        let res = crate::metrics::external(
+            "devforum",
+            self.request
+                .get(endpoint)
+                .header(COOKIE, format!("_t={devforum_cookie}")),
+        )
+        .await?;
+
+        match res.status() {
+            status if status.is_success() => res
+                .json::<DevForumAPIResponse>()
+                .await
+                .map(DevForumProfile::Visible)
+                .context("parse devforum data with cookie"),
+            StatusCode::NOT_FOUND => Ok(DevForumProfile::Missing),
+            StatusCode::FORBIDDEN => Ok(DevForumProfile::Hidden),
+            status => anyhow::bail!(
+                "Failed to fetch DevForum data for roblox_username={roblox_username} with cookie, received status: {status}"
+            ),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct RoVerAPIResponse {
+    #[serde(rename = "robloxId")]
+    pub(crate) roblox_id: u64,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct RobloxAPIResponse {
+    pub(crate) name: String,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct DevForumAPIResponse {
+    pub(crate) user: DevForumUser,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct DevForumUser {
+    pub(crate) trust_level: DevForumTrustLevel,
+}
+
+#[derive(Deserialize_repr, Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub(crate) enum DevForumTrustLevel {
+    Visitor = 0,
+    Member = 1,
+    Regular = 2,
+    Staff,
+}
+
+impl std::fmt::Display for DevForumTrustLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DevForumTrustLevel::Visitor => write!(f, "Visitor"),
+            DevForumTrustLevel::Member => write!(f, "Member"),
+            DevForumTrustLevel::Regular => write!(f, "Regular"),
+            DevForumTrustLevel::Staff => write!(f, "Staff"),
+        }
+    }
+}
+
+/// A local server standing in for the external APIs, serving the JSON fixtures.
+#[cfg(test)]
+pub(crate) mod stand_in {
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::get;
+    use axum::Router;
+
+    use super::ApiUrls;
+
+    pub(crate) const ROVER: &str = include_str!("../tests/fixtures/rover.json");
+    pub(crate) const ROBLOX_USER: &str = include_str!("../tests/fixtures/roblox_user.json");
+    pub(crate) const DEVFORUM_USER: &str = include_str!("../tests/fixtures/devforum_user.json");
+
+    /// The routes of the external APIs, responding with the fixtures.
+    pub(crate) fn routes() -> Router {
+        Router::new()
+            .route(
+                "/api/guilds/{guild_id}/discord-to-roblox/{discord_id}",
+                get(|headers: HeaderMap| async move {
+                    match headers.get("authorization") {
+                        Some(_) => (StatusCode::OK, ROVER),
+                        None => (StatusCode::UNAUTHORIZED, "{}"),
+                    }
+                }),
+            )
+            .route("/v1/users/{roblox_id}", get(|| async { ROBLOX_USER }))
+            .route("/u/{username}", get(|| async { DEVFORUM_USER }))
+    }
+
+    /// Serves the given routes on a random local port, returning the URLs of the APIs.
+    pub(crate) async fn serve(routes: Router) -> ApiUrls {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind stand-in server");
+        let address = listener.local_addr().expect("get stand-in server address");
+        tokio::spawn(async move { axum::serve(listener, routes).await });
+
+        ApiUrls::stand_in(&format!("http://{address}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api(urls: ApiUrls) -> HttpApi {
+        HttpApi {
+            rover_api_key: Some("key".to_string()),
+            devforum_cookie: None,
+            ..HttpApi::new(urls, &SecretsConfig::default())
+        }
+    }
+
+    #[tokio::test]
+    async fn fetches_from_stand_in() {
+        let api = api(stand_in::serve(stand_in::routes()).await);
+
+        let rover = api
+            .fetch_rover_data(Id::new(1), Id::new(2))
+            .await
+            .unwrap()
+            .unwrap();
+        let roblox = api.fetch_roblox_data(rover.roblox_id).await.unwrap();
+        let DevForumProfile::Visible(devforum) =
+            api.fetch_devforum_data(&roblox.name).await.unwrap()
+        else {
+            panic!("expected visible devforum profile");
+        };
+
+        assert_eq!(rover.roblox_id, 1);
+        assert_eq!(roblox.name, "Roblox");
+        assert_eq!(devforum.user.trust_level, DevForumTrustLevel::Regular);
+    }
+
+    #[tokio::test]
+    async fn missing_rover_key() {
+        let api = HttpApi {
+            rover_api_key: None,
+            ..api(stand_in::serve(stand_in::routes()).await)
+        };
+
+        assert!(api.fetch_rover_data(Id::new(1), Id::new(2)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn error_status() {
+        let api = api(stand_in::serve(axum::Router::new()).await);
+
+        assert!(api.fetch_roblox_data(1).await.is_err());
+        // Unlinked users and users without a DevForum account aren't found
+        assert!(api
+            .fetch_rover_data(Id::new(1), Id::new(2))
+            .await
+            .unwrap()
+            .is_none());
+        assert!(matches!(
+            api.fetch_devforum_data("Roblox").await.unwrap(),
+            DevForumProfile::Missing
+        ));
+
+        let routes = axum::Router::new().fallback(|| async { axum::http::StatusCode::FORBIDDEN });
+        let api = self::api(stand_in::serve(routes).await);
+        assert!(matches!(
+            api.fetch_devforum_data("Roblox").await.unwrap(),
+            DevForumProfile::Hidden
+        ));
+
+        let routes = axum::Router::new()
+            .fallback(|| async { axum::http::StatusCode::INTERNAL_SERVER_ERROR });
+        let api = self::api(stand_in::serve(routes).await);
+        assert!(api.fetch_devforum_data("Roblox").await.is_err());
+    }
+}
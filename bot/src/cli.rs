@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+/// The default path of the config file.
+const DEFAULT_CONFIG_PATH: &str = "magnolia.cfg.yml";
+
+/// Command line interface of the bot.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub(crate) struct Cli {
+    /// The path of the config file.
+    #[arg(short, long, global = true, default_value = DEFAULT_CONFIG_PATH)]
+    pub(crate) config: PathBuf,
+    /// The action to perform, defaults to running the bot.
+    #[command(subcommand)]
+    pub(crate) command: Option<CliCommand>,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum CliCommand {
+    /// Connect to the gateway and handle interactions.
+    Run,
+    /// Check that the config file can be loaded.
+    ValidateConfig {
+        /// The path of the config file, overrides `--config`.
+        path: Option<PathBuf>,
+    },
+    /// Print the JSON Schema of the config file.
+    ConfigSchema,
+    /// Manage the application commands without connecting to the gateway.
+    #[command(subcommand)]
+    Commands(CommandsAction),
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum CommandsAction {
+    /// Print the application command models as JSON.
+    Dump,
+    /// Publish the application commands that differ from the published ones.
+    Publish,
+    /// Delete all published application commands.
+    Clear,
+}
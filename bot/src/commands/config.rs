@@ -1,135 +1,392 @@
-use std::fmt::Display;
 use std::str::FromStr;
 
 use anyhow::Context;
 use async_trait::async_trait;
-use builders::command_option::CommandOptionBuilder;
 use twilight_model::application::command::{
     Command, CommandOptionChoice, CommandOptionChoiceValue, CommandOptionType, CommandType,
 };
-use twilight_model::application::interaction::application_command::CommandOptionValue;
+use twilight_model::application::interaction::application_command::{
+    CommandDataOption, CommandOptionValue,
+};
 use twilight_model::application::interaction::{
     Interaction, InteractionContextType, InteractionData,
 };
 use twilight_model::guild::Permissions;
 use twilight_model::http::attachment::Attachment;
-use twilight_model::http::interaction::{InteractionResponse, InteractionResponseType};
+use twilight_model::id::marker::RoleMarker;
+use twilight_model::id::Id;
 use twilight_model::oauth::ApplicationIntegrationType;
-use twilight_util::builder::command::CommandBuilder;
 use twilight_util::builder::InteractionResponseDataBuilder;
 
+use crate::access::Access;
 use crate::commands::CommandHandler;
-use crate::config::config_path;
+use crate::config::edit::{self, Setting, SETTINGS};
+use crate::config::format::ExportFormat;
+use crate::error::InteractionError;
 
 #[allow(dead_code)]
 pub(crate) struct Config<'a> {
     pub(crate) cmd: &'a Interaction,
 }
 
+const FILE_TYPE_OPTION_NAME: &str = "file_type";
+const SETTING_OPTION_NAME: &str = "setting";
+const VALUE_OPTION_NAME: &str = "value";
+
 #[async_trait]
 impl CommandHandler for Config<'_> {
     fn model(_ctx: Option<crate::Context>) -> anyhow::Result<Command> {
-        let file_type_choices = [
-            CommandOptionChoice {
-                name: "Rust".to_string(),
-                value: CommandOptionChoiceValue::String(FileType::Rust.to_string()),
-                name_localizations: None,
-            },
-            CommandOptionChoice {
-                name: "YAML".to_string(),
-                value: CommandOptionChoiceValue::String(FileType::Yaml.to_string()),
-                name_localizations: None,
-            },
-        ];
-        let file_type_option = CommandOptionBuilder::new(
-            "file_type",
-            "The type of file to send",
+        let file_type_choices = ExportFormat::ALL.map(|format| CommandOptionChoice {
+            name: format.to_string(),
+            value: CommandOptionChoiceValue::String(format.to_string()),
+            name_localizations: None,
+        });
+        let file_type_option = super::option(
+            FILE_TYPE_OPTION_NAME,
+            "commands.config.options.file_type",
             CommandOptionType::String,
         )
         .required(true)
         .choices(file_type_choices)
         .build()?;
 
-        Ok(CommandBuilder::new(
-            "config",
-            "Send quick responses to common questions/queries.",
-            CommandType::ChatInput,
+        let setting_choices = SETTINGS.iter().map(|setting| CommandOptionChoice {
+            name: setting.path.to_string(),
+            value: CommandOptionChoiceValue::String(setting.path.to_string()),
+            name_localizations: None,
+        });
+        let setting_option = super::option(
+            SETTING_OPTION_NAME,
+            "commands.config.options.setting",
+            CommandOptionType::String,
+        )
+        .required(true)
+        .choices(setting_choices)
+        .build()?;
+        let value_option = super::option(
+            VALUE_OPTION_NAME,
+            "commands.config.options.value",
+            CommandOptionType::String,
+        )
+        .required(true)
+        .build()?;
+
+        let export = super::option(
+            "export",
+            "commands.config.export",
+            CommandOptionType::SubCommand,
         )
-        .contexts([InteractionContextType::Guild])
-        .integration_types([ApplicationIntegrationType::GuildInstall])
-        .default_member_permissions(Permissions::MANAGE_CHANNELS)
         .option(file_type_option)
-        .validate()
-        .context("validate config command")?
-        .build())
+        .build()?;
+        let get = super::option("get", "commands.config.get", CommandOptionType::SubCommand)
+            .option(setting_option.clone())
+            .build()?;
+        let set = super::option("set", "commands.config.set", CommandOptionType::SubCommand)
+            .option(setting_option)
+            .option(value_option)
+            .build()?;
+        let diff = super::option(
+            "diff",
+            "commands.config.diff",
+            CommandOptionType::SubCommand,
+        )
+        .build()?;
+
+        Ok(super::command("config", CommandType::ChatInput)
+            .contexts([InteractionContextType::Guild])
+            .integration_types([ApplicationIntegrationType::GuildInstall])
+            .default_member_permissions(Permissions::MANAGE_CHANNELS)
+            .option(export)
+            .option(get)
+            .option(set)
+            .option(diff)
+            .validate()
+            .context("validate config command")?
+            .build())
+    }
+
+    fn access(&self) -> Access {
+        Access::new().permissions(Permissions::MANAGE_CHANNELS)
     }
 
     async fn exec(&self, ctx: crate::Context) -> anyhow::Result<()> {
-        // Parse the given file type from command options
         let Some(InteractionData::ApplicationCommand(data)) = &self.cmd.data else {
             anyhow::bail!("expected application command interaction");
         };
-        let CommandOptionValue::String(file_type) = &data.options[0].value else {
-            anyhow::bail!("expected string option value");
+        let subcommand = data.options.first().context("missing subcommand")?;
+        let CommandOptionValue::SubCommand(options) = &subcommand.value else {
+            anyhow::bail!("expected subcommand option");
         };
-        let file_type =
-            FileType::from_str(file_type).context("failed to parse file type from string")?;
-
-        // Create the config file based on the file type
-        let cfg_file = match file_type {
-            FileType::Rust => {
-                // Get the stored config struct
-                let content = format!("{:#?}", ctx.cfg).into_bytes();
-                Attachment::from_bytes("magnolia.cfg.rs".to_string(), content, 0)
+
+        match subcommand.name.as_str() {
+            "export" => export(&ctx, options).await,
+            "get" => {
+                let setting = setting(&ctx, options)?;
+                let value = setting.get(&ctx.cfg());
+                let content = ctx.message("commands.config.get.reply", &[
+                    ("setting", &setting.path),
+                    ("value", &value),
+                ]);
+                ctx.responder()?.reply_ephemeral(content).await
             },
-            FileType::Yaml => {
-                // Get the raw content of the config file
-                let content = std::fs::read(config_path())?;
-                Attachment::from_bytes("magnolia.cfg.yml".to_string(), content, 0)
+            "set" => set(&ctx, self.cmd, options).await,
+            "diff" => {
+                let changes = ctx.config.diff();
+                let content = if changes.is_empty() {
+                    ctx.message("commands.config.diff.unchanged", &[])
+                } else {
+                    changes
+                        .iter()
+                        .map(|change| {
+                            ctx.message("commands.config.diff.line", &[
+                                ("setting", &change.path),
+                                ("initial", &change.initial),
+                                ("current", &change.current),
+                            ])
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                };
+                ctx.responder()?.reply_ephemeral(content).await
             },
-        };
+            name => anyhow::bail!("unknown config subcommand: {name}"),
+        }
+    }
+}
 
-        // Respond to the interaction with the config file
-        ctx.http
-            .interaction(self.cmd.application_id)
-            .create_response(self.cmd.id, &self.cmd.token, &InteractionResponse {
-                kind: InteractionResponseType::ChannelMessageWithSource,
-                data: Some(
-                    InteractionResponseDataBuilder::new()
-                        .attachments([cfg_file])
-                        .build(),
-                ),
-            })
-            .await
-            .context("failed to send config file")?;
+/// Responds with the current config as a file in the chosen format.
+async fn export(ctx: &crate::Context, options: &[CommandDataOption]) -> anyhow::Result<()> {
+    let file_type = string_option(options, FILE_TYPE_OPTION_NAME)?;
+    let file_type =
+        ExportFormat::from_str(file_type).context("failed to parse file type from string")?;
 
-        Ok(())
-    }
+    // Export the live config, which never includes the secrets
+    let content = ctx.cfg().export(file_type)?.into_bytes();
+    let cfg_file = Attachment::from_bytes(file_type.file_name().to_string(), content, 0);
+
+    // Respond to the interaction with the config file
+    ctx.responder()?
+        .reply(
+            InteractionResponseDataBuilder::new()
+                .attachments([cfg_file])
+                .build(),
+        )
+        .await
+        .context("failed to send config file")
 }
 
-/// FileType enum to represent the type of config file to send
-enum FileType {
-    Rust,
-    Yaml,
+/// Changes the setting to the value in the options, if the member can assign its roles.
+async fn set(
+    ctx: &crate::Context,
+    cmd: &Interaction,
+    options: &[CommandDataOption],
+) -> anyhow::Result<()> {
+    // The roles of the config grant access and bypass cooldowns, like managing roles does.
+    Access::new().permissions(Permissions::MANAGE_ROLES).check(
+        cmd.member.as_ref(),
+        &ctx.cfg().roles,
+        &ctx.locale,
+    )?;
+
+    let setting = setting(ctx, options)?;
+    let value = string_option(options, VALUE_OPTION_NAME)?;
+    let edited = edit::apply(&ctx.cfg(), setting, value)
+        .map_err(|error| InteractionError::user(error.message(&ctx.locale)))?;
+    check_hierarchy(ctx, cmd, &setting.roles(&edited)).await?;
+
+    let cfg = ctx.config.set(setting, value, &ctx.locale).await?;
+    let content = ctx.message("commands.config.set.reply", &[
+        ("setting", &setting.path),
+        ("value", &setting.get(&cfg)),
+    ]);
+    ctx.responder()?.reply_ephemeral(content).await
 }
 
-impl Display for FileType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            FileType::Rust => write!(f, "Rust"),
-            FileType::Yaml => write!(f, "YAML"),
+/// Checks that the roles are below the highest role of the member,
+/// as Discord requires to assign roles.
+async fn check_hierarchy(
+    ctx: &crate::Context,
+    cmd: &Interaction,
+    roles: &[Id<RoleMarker>],
+) -> anyhow::Result<()> {
+    if roles.is_empty() {
+        return Ok(());
+    }
+    let guild_id = cmd.guild_id.context("missing guild id")?;
+    let member = cmd.member.as_ref().context("missing member")?;
+
+    let guild_roles = crate::metrics::discord("roles", ctx.http.roles(guild_id))
+        .await
+        .context("get guild roles")?
+        .models()
+        .await
+        .context("deserialize guild roles")?;
+    // Members without roles only have the `@everyone` role, at the bottom.
+    let highest = guild_roles
+        .iter()
+        .filter(|role| member.roles.contains(&role.id))
+        .map(|role| role.position)
+        .max()
+        .unwrap_or(0);
+
+    for id in roles {
+        let Some(role) = guild_roles.iter().find(|role| role.id == *id) else {
+            let message = ctx.message("commands.config.set.unknown_role", &[("role", id)]);
+            return Err(InteractionError::user(message).into());
+        };
+        if role.position >= highest {
+            let message = ctx.message("commands.config.set.role_too_high", &[("role", id)]);
+            return Err(InteractionError::permission(message).into());
         }
     }
+    Ok(())
+}
+
+/// Returns the setting chosen in the options.
+fn setting(
+    ctx: &crate::Context,
+    options: &[CommandDataOption],
+) -> anyhow::Result<&'static Setting> {
+    let path = string_option(options, SETTING_OPTION_NAME)?;
+    edit::setting(path).ok_or_else(|| {
+        InteractionError::user(
+            ctx.message("commands.config.unknown_setting", &[("setting", &path)]),
+        )
+        .into()
+    })
+}
+
+/// Returns the value of the string option with the given name.
+fn string_option<'a>(options: &'a [CommandDataOption], name: &str) -> anyhow::Result<&'a str> {
+    let option = options
+        .iter()
+        .find(|opt| opt.name == name)
+        .with_context(|| format!("missing {name} option"))?;
+    let CommandOptionValue::String(value) = &option.value else {
+        anyhow::bail!("expected string {name} option");
+    };
+    Ok(value)
 }
 
-impl FromStr for FileType {
-    type Err = anyhow::Error;
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "Rust" => Ok(FileType::Rust),
-            "YAML" => Ok(FileType::Yaml),
-            _ => Err(anyhow::anyhow!("Invalid file type")),
-        }
+    use serde_json::json;
+    use twilight_model::guild::Permissions;
+
+    use crate::config::live::LiveConfig;
+    use crate::testing::{Simulation, TempDir};
+
+    #[tokio::test]
+    async fn sends_config_file() {
+        let sim = Simulation::new()
+            .await
+            .member(&[], Permissions::MANAGE_CHANNELS);
+
+        sim.run(&sim.command(
+            "config",
+            json!([{
+                "name": "export",
+                "type": 1,
+                "options": [{ "name": "file_type", "type": 3, "value": "YAML" }],
+            }]),
+        ))
+        .await
+        .unwrap();
+
+        let responses = sim.responses();
+        assert_eq!(
+            responses[0]["data"]["attachments"][0]["filename"],
+            "magnolia.cfg.yml"
+        );
+    }
+
+    #[tokio::test]
+    async fn edits_config_file() {
+        let dir = TempDir::new("config-edit");
+        let source = include_str!("../../tests/fixtures/config.yml");
+        let path = dir.write("magnolia.cfg.yml", source);
+
+        let mut sim = Simulation::new()
+            .await
+            .member(&[], Permissions::MANAGE_CHANNELS);
+        let cfg = crate::config::load_config_with(&path, |_| Ok(())).unwrap();
+        sim.ctx.config = Arc::new(LiveConfig::new(cfg, path.clone()));
+
+        let set = |sim: &Simulation, setting: &str, value: &str| {
+            sim.command(
+                "config",
+                json!([{
+                    "name": "set",
+                    "type": 1,
+                    "options": [
+                        { "name": "setting", "type": 3, "value": setting },
+                        { "name": "value", "type": 3, "value": value },
+                    ],
+                }]),
+            )
+        };
+
+        // Setting roles requires the permission to manage them.
+        sim.run(&set(&sim, "roles.devforum_member", "1004"))
+            .await
+            .unwrap();
+        assert!(sim.last_content().unwrap().contains("`Manage Roles`"));
+        let sim = sim.member(
+            &[1005],
+            Permissions::MANAGE_CHANNELS | Permissions::MANAGE_ROLES,
+        );
+
+        // Invalid values are rejected without changing the config.
+        sim.run(&set(&sim, "roles.devforum_member", "1002"))
+            .await
+            .unwrap();
+        assert!(sim
+            .last_content()
+            .unwrap()
+            .contains("role `1002` is also used by roles.devforum_member"));
+        sim.run(&set(&sim, "roles.cooldown_bypass", "1003 1001"))
+            .await
+            .unwrap();
+        assert!(sim
+            .last_content()
+            .unwrap()
+            .contains("role `1001` is also used by roles.devforum_member"));
+
+        // Members can't set roles at or above their highest one.
+        sim.run(&set(&sim, "roles.devforum_member", "1005"))
+            .await
+            .unwrap();
+        assert!(sim.last_content().unwrap().contains("<@&1005> isn't."));
+        sim.run(&set(&sim, "roles.devforum_member", "2000"))
+            .await
+            .unwrap();
+        assert!(sim
+            .last_content()
+            .unwrap()
+            .contains("<@&2000> isn't a role of this server."));
+        assert_eq!(sim.ctx.cfg().roles.devforum_member.get(), 1001);
+
+        sim.run(&set(&sim, "roles.devforum_member", "<@&1004>"))
+            .await
+            .unwrap();
+        assert_eq!(sim.ctx.cfg().roles.devforum_member.get(), 1004);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            source.replace("devforum_member: \"1001\"", "devforum_member: \"1004\"")
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path("magnolia.cfg.yml.bak")).unwrap(),
+            source
+        );
+
+        sim.run(&sim.command("config", json!([{ "name": "diff", "type": 1 }])))
+            .await
+            .unwrap();
+        assert_eq!(
+            sim.last_content().unwrap(),
+            "`roles.devforum_member`: `\"1001\"` → `\"1004\"`"
+        );
     }
 }
@@ -3,18 +3,16 @@ use async_trait::async_trait;
 use builders::component::ActionRowBuilder;
 use twilight_model::application::command::{Command, CommandType};
 use twilight_model::application::interaction::{Interaction, InteractionContextType};
-use twilight_model::channel::message::MessageFlags;
 use twilight_model::guild::Permissions;
 use twilight_model::http::attachment::Attachment;
-use twilight_model::http::interaction::{InteractionResponse, InteractionResponseType};
 use twilight_model::oauth::ApplicationIntegrationType;
-use twilight_util::builder::command::CommandBuilder;
 use twilight_util::builder::embed::{EmbedBuilder, ImageSource};
-use twilight_util::builder::InteractionResponseDataBuilder;
 
+use crate::access::Access;
 use crate::commands::CommandHandler;
 use crate::components::verify_devforum_rank::VerifyDevForumRank;
 use crate::components::ComponentHandler;
+use crate::responder::Defer;
 
 #[allow(dead_code)]
 pub(crate) struct DevForumSelfRole<'a> {
@@ -24,17 +22,21 @@ pub(crate) struct DevForumSelfRole<'a> {
 #[async_trait]
 impl CommandHandler for DevForumSelfRole<'_> {
     fn model(_ctx: Option<crate::Context>) -> anyhow::Result<Command> {
-        Ok(CommandBuilder::new(
-            "devforum-self-role",
-            "Send an info embed with a button to self-update DevForum roles.",
-            CommandType::ChatInput,
-        )
-        .contexts([InteractionContextType::Guild])
-        .integration_types([ApplicationIntegrationType::GuildInstall])
-        .default_member_permissions(Permissions::MANAGE_CHANNELS)
-        .validate()
-        .context("validate devforum-self-role command")?
-        .build())
+        Ok(super::command("devforum-self-role", CommandType::ChatInput)
+            .contexts([InteractionContextType::Guild])
+            .integration_types([ApplicationIntegrationType::GuildInstall])
+            .default_member_permissions(Permissions::MANAGE_CHANNELS)
+            .validate()
+            .context("validate devforum-self-role command")?
+            .build())
+    }
+
+    fn defer(&self) -> Defer {
+        Defer::Ephemeral
+    }
+
+    fn access(&self) -> Access {
+        Access::new().permissions(Permissions::MANAGE_CHANNELS)
     }
 
     async fn exec(&self, ctx: crate::Context) -> anyhow::Result<()> {
@@ -43,49 +45,41 @@ impl CommandHandler for DevForumSelfRole<'_> {
             Vec::from(include_bytes!("../../../assets/devforum-logo.png")),
             0,
         );
+        let roles = &ctx.cfg().roles;
         let info_embed = EmbedBuilder::new()
-            .title("Developer Forum Member Role(s)")
-            .description(format!(
-                "Click the `Update Roles` button below to claim your developer forum member role if you meet the eligibility criteria.
-
-- <@&{}> - Your **trust level** on the Roblox developer forum is `Member` (not to be confused with `Visitor`)
-- <@&{}> - Your **trust level** on the Roblox developer forum is `Regular`
-- What is the developer forum? [**Learn more**](https://help.roblox.com/hc/articles/360000240223)
-- How do I \"level up\"? [**Learn more**](https://devforum.roblox.com/t/3170997)",
-                ctx.cfg.roles.devforum_member,
-                ctx.cfg.roles.devforum_regular
-            ))
+            .title(ctx.guild_message("commands.devforum-self-role.embed.title", &[]))
+            .description(
+                ctx.guild_message("commands.devforum-self-role.embed.description", &[
+                    (
+                        "button",
+                        &ctx.guild_message("components.verify-devforum-rank.label", &[]),
+                    ),
+                    ("member", &roles.devforum_member),
+                    ("regular", &roles.devforum_regular),
+                ]),
+            )
             .thumbnail(ImageSource::attachment(&devforum_logo.filename)?)
             .build();
         let action_row = ActionRowBuilder::new()
-            .set_components([VerifyDevForumRank::model()?])
+            .set_components([VerifyDevForumRank::model(Some(ctx.clone()))?])
             .build()
             .context("build action row")?;
 
         // Send the embed to the channel
         let channel_id = self.cmd.channel.as_ref().context("get channel id")?.id;
-        ctx.http
-            .create_message(channel_id)
-            .embeds(&[info_embed])
-            .components(&[action_row])
-            .attachments(&[devforum_logo])
-            .await?;
-
-        // Create the interaction response
-        let response = InteractionResponse {
-            kind: InteractionResponseType::ChannelMessageWithSource,
-            data: Some(
-                InteractionResponseDataBuilder::new()
-                    .content("Successfully sent the self-role embed.")
-                    .flags(MessageFlags::EPHEMERAL)
-                    .build(),
-            ),
-        };
+        crate::metrics::discord(
+            "create_message",
+            ctx.http
+                .create_message(channel_id)
+                .embeds(&[info_embed])
+                .components(&[action_row])
+                .attachments(&[devforum_logo]),
+        )
+        .await?;
 
         // Respond to the interaction ephemerally
-        ctx.http
-            .interaction(self.cmd.application_id)
-            .create_response(self.cmd.id, &self.cmd.token, &response)
+        ctx.responder()?
+            .reply_ephemeral(ctx.message("commands.devforum-self-role.sent", &[]))
             .await
             .context("create response")?;
 
@@ -0,0 +1,157 @@
+use std::fmt::Display;
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use twilight_model::application::command::{Command, CommandOptionType, CommandType};
+use twilight_model::application::interaction::application_command::CommandOptionValue;
+use twilight_model::application::interaction::{
+    Interaction, InteractionContextType, InteractionData,
+};
+use twilight_model::guild::Permissions;
+use twilight_model::id::marker::UserMarker;
+use twilight_model::id::Id;
+use twilight_model::oauth::ApplicationIntegrationType;
+
+use crate::access::Access;
+use crate::commands::CommandHandler;
+use crate::cooldown::{Bucket, Cooldown};
+use crate::devforum::{self, Verification};
+use crate::responder::Defer;
+
+const MEMBER_OPTION_NAME: &str = "member";
+
+pub(crate) struct DevForumVerify<'a> {
+    pub(crate) cmd: &'a Interaction,
+}
+
+#[async_trait]
+impl CommandHandler for DevForumVerify<'_> {
+    fn model(_ctx: Option<crate::Context>) -> anyhow::Result<Command> {
+        let member_option = super::option(
+            MEMBER_OPTION_NAME,
+            "commands.devforum-verify.options.member",
+            CommandOptionType::User,
+        )
+        .required(true)
+        .build()?;
+
+        Ok(super::command("devforum-verify", CommandType::ChatInput)
+            .contexts([InteractionContextType::Guild])
+            .integration_types([ApplicationIntegrationType::GuildInstall])
+            .default_member_permissions(Permissions::MANAGE_ROLES)
+            .option(member_option)
+            .validate()
+            .context("validate devforum-verify command")?
+            .build())
+    }
+
+    fn access(&self) -> Access {
+        Access::new().permissions(Permissions::MANAGE_ROLES)
+    }
+
+    fn cooldown(&self) -> Option<Cooldown> {
+        Some(Cooldown::new(Bucket::User, Duration::from_secs(10)))
+    }
+
+    fn defer(&self) -> Defer {
+        Defer::Ephemeral
+    }
+
+    async fn exec(&self, ctx: crate::Context) -> anyhow::Result<()> {
+        let Some(InteractionData::ApplicationCommand(data)) = &self.cmd.data else {
+            anyhow::bail!("expected application command interaction");
+        };
+        let guild_id = self.cmd.guild_id.context("get guild id")?;
+        let member = data
+            .options
+            .iter()
+            .find(|opt| opt.name == MEMBER_OPTION_NAME)
+            .context("missing member option")?;
+        let CommandOptionValue::User(user_id) = member.value else {
+            anyhow::bail!("expected user member option");
+        };
+
+        let verification = devforum::verify(&ctx, guild_id, user_id).await;
+        ctx.responder()?
+            .reply_ephemeral(response_content(&ctx, user_id, &verification))
+            .await
+    }
+}
+
+/// Returns the message telling the moderator the outcome of the verification of the member.
+fn response_content(
+    ctx: &crate::Context,
+    user_id: Id<UserMarker>,
+    verification: &Verification,
+) -> String {
+    let key = |name: &str| format!("commands.devforum-verify.{name}");
+    let user: (&str, &dyn Display) = ("user", &user_id);
+    match verification {
+        Verification::NotLinked => ctx.message(&key("not_linked"), &[user]),
+        Verification::RoVerLookupFailed
+        | Verification::RobloxLookupFailed
+        | Verification::DevForumLookupFailed => ctx.message(&key("lookup_failed"), &[user]),
+        Verification::ProfileHidden => ctx.message(&key("profile_hidden"), &[user]),
+        Verification::NoDevForumAccount => ctx.message(&key("no_devforum_account"), &[user]),
+        Verification::RolesUpdated {
+            trust_level,
+            update,
+        } => {
+            let content = ctx.message(&key("updated"), &[user, ("trust_level", trust_level)]);
+            format!("{content}\n{}", update.lines(ctx))
+        },
+        Verification::RolesUnchanged { trust_level } => {
+            ctx.message(&key("unchanged"), &[user, ("trust_level", trust_level)])
+        },
+        Verification::RoleUpdateFailed { .. } => ctx.message(&key("update_failed"), &[user]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use twilight_model::guild::Permissions;
+
+    use crate::testing::{Simulation, USER_ID};
+
+    #[tokio::test]
+    async fn updates_member_roles() {
+        // The stand-in server responds with the member that interacts, a DevForum member
+        let sim = Simulation::new()
+            .await
+            .member(&[1001], Permissions::MANAGE_ROLES);
+
+        sim.run(&sim.command(
+            "devforum-verify",
+            json!([{ "name": "member", "type": 6, "value": USER_ID.to_string() }]),
+        ))
+        .await
+        .unwrap();
+
+        assert_eq!(
+            sim.last_content().as_deref(),
+            Some(
+                "Updated the roles of <@300> to match their DevForum trust level: `Regular`\n\
+                 - Added <@&1002>\n\
+                 - Removed <@&1001>"
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn requires_manage_roles() {
+        let sim = Simulation::new()
+            .await
+            .member(&[1001], Permissions::MANAGE_CHANNELS);
+
+        sim.run(&sim.command(
+            "devforum-verify",
+            json!([{ "name": "member", "type": 6, "value": USER_ID.to_string() }]),
+        ))
+        .await
+        .unwrap();
+
+        assert!(sim.calls().iter().all(|call| call.method != "PATCH"));
+    }
+}
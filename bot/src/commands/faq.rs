@@ -1,17 +1,16 @@
 use anyhow::Context;
 use async_trait::async_trait;
-use builders::command_option::CommandOptionBuilder;
 use twilight_model::application::command::{Command, CommandOptionType, CommandType};
 use twilight_model::application::interaction::application_command::CommandOptionValue;
 use twilight_model::application::interaction::{
     Interaction, InteractionContextType, InteractionData,
 };
 use twilight_model::guild::Permissions;
-use twilight_model::http::interaction::{InteractionResponse, InteractionResponseType};
 use twilight_model::oauth::ApplicationIntegrationType;
-use twilight_util::builder::command::CommandBuilder;
 
+use crate::access::Access;
 use crate::commands::CommandHandler;
+use crate::error::InteractionError;
 
 const QUERY_OPTION_NAME: &str = "query";
 const MENTION_OPTION_NAME: &str = "mention";
@@ -25,35 +24,35 @@ pub(crate) struct Faq<'a> {
 impl CommandHandler for Faq<'_> {
     fn model(ctx: Option<crate::Context>) -> anyhow::Result<Command> {
         let ctx = ctx.expect("ctx is required");
-        let query_option = CommandOptionBuilder::new(
+        let query_option = super::option(
             QUERY_OPTION_NAME,
-            "The response to send.",
+            "commands.faq.options.query",
             CommandOptionType::String,
         )
-        .choices(ctx.cfg.faq_option_choices())
+        .choices(ctx.cfg().faq_option_choices())
         .required(true)
         .build()?;
 
-        let mention_option = CommandOptionBuilder::new(
+        let mention_option = super::option(
             MENTION_OPTION_NAME,
-            "The user to mention in the response.",
+            "commands.faq.options.mention",
             CommandOptionType::User,
         )
         .build()?;
 
-        Ok(CommandBuilder::new(
-            "faq",
-            "Send quick responses to common questions/queries.",
-            CommandType::ChatInput,
-        )
-        .contexts([InteractionContextType::Guild])
-        .integration_types([ApplicationIntegrationType::GuildInstall])
-        .default_member_permissions(Permissions::MANAGE_CHANNELS)
-        .option(query_option)
-        .option(mention_option)
-        .validate()
-        .context("validate faq command")?
-        .build())
+        Ok(super::command("faq", CommandType::ChatInput)
+            .contexts([InteractionContextType::Guild])
+            .integration_types([ApplicationIntegrationType::GuildInstall])
+            .default_member_permissions(Permissions::MANAGE_CHANNELS)
+            .option(query_option)
+            .option(mention_option)
+            .validate()
+            .context("validate faq command")?
+            .build())
+    }
+
+    fn access(&self) -> Access {
+        Access::new().permissions(Permissions::MANAGE_CHANNELS)
     }
 
     async fn exec(&self, ctx: crate::Context) -> anyhow::Result<()> {
@@ -69,8 +68,11 @@ impl CommandHandler for Faq<'_> {
         let CommandOptionValue::String(query) = &query.value else {
             anyhow::bail!("expected string query option");
         };
-        let Some(mut response) = ctx.cfg.faq_option_response(query) else {
-            anyhow::bail!("unknown query option: {}", query);
+        let Some(mut response) = ctx.cfg().faq_option_response(query) else {
+            return Err(InteractionError::user(
+                ctx.message("commands.faq.unknown_option", &[("value", query)]),
+            )
+            .into());
         };
 
         // Add mention if provided
@@ -92,14 +94,70 @@ impl CommandHandler for Faq<'_> {
         }
 
         // Send the response
-        ctx.http
-            .interaction(self.cmd.application_id)
-            .create_response(self.cmd.id, &self.cmd.token, &InteractionResponse {
-                kind: InteractionResponseType::ChannelMessageWithSource,
-                data: Some(response),
-            })
-            .await?;
+        ctx.responder()?.reply(response).await?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use twilight_model::guild::Permissions;
+
+    use crate::testing::Simulation;
+
+    #[tokio::test]
+    async fn responds_with_option() {
+        let sim = Simulation::new()
+            .await
+            .member(&[], Permissions::MANAGE_CHANNELS);
+
+        sim.run(&sim.command(
+            "faq",
+            json!([
+                { "name": "query", "type": 3, "value": "devforum-roles" },
+                { "name": "mention", "type": 6, "value": "42" },
+            ]),
+        ))
+        .await
+        .unwrap();
+
+        assert_eq!(
+            sim.last_content().as_deref(),
+            Some("<@42> Click the **Update Roles** button in the roles channel.")
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_option() {
+        let sim = Simulation::new()
+            .await
+            .member(&[], Permissions::MANAGE_CHANNELS);
+
+        sim.run(&sim.command(
+            "faq",
+            json!([{ "name": "query", "type": 3, "value": "missing" }]),
+        ))
+        .await
+        .unwrap();
+
+        let content = sim.last_content().unwrap();
+        assert!(content.starts_with("Unknown FAQ option: `missing`"));
+    }
+
+    #[tokio::test]
+    async fn requires_permissions() {
+        let sim = Simulation::new().await;
+
+        sim.run(&sim.command(
+            "faq",
+            json!([{ "name": "query", "type": 3, "value": "devforum-roles" }]),
+        ))
+        .await
+        .unwrap();
+
+        let content = sim.last_content().unwrap();
+        assert!(content.starts_with("You are missing the following permissions"));
+    }
+}
@@ -0,0 +1,54 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use builders::component::ActionRowBuilder;
+use twilight_model::application::command::{Command, CommandType};
+use twilight_model::application::interaction::{Interaction, InteractionContextType};
+use twilight_model::guild::Permissions;
+use twilight_model::oauth::ApplicationIntegrationType;
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::access::Access;
+use crate::commands::CommandHandler;
+use crate::components::faq_browser::FaqBrowser as FaqBrowserMenu;
+use crate::components::ComponentHandler;
+
+#[allow(dead_code)]
+pub(crate) struct FaqBrowser<'a> {
+    pub(crate) cmd: &'a Interaction,
+}
+
+#[async_trait]
+impl CommandHandler for FaqBrowser<'_> {
+    fn model(_ctx: Option<crate::Context>) -> anyhow::Result<Command> {
+        Ok(super::command("faq-browser", CommandType::ChatInput)
+            .contexts([InteractionContextType::Guild])
+            .integration_types([ApplicationIntegrationType::GuildInstall])
+            .default_member_permissions(Permissions::MANAGE_CHANNELS)
+            .validate()
+            .context("validate faq-browser command")?
+            .build())
+    }
+
+    fn access(&self) -> Access {
+        Access::new().permissions(Permissions::MANAGE_CHANNELS)
+    }
+
+    async fn exec(&self, ctx: crate::Context) -> anyhow::Result<()> {
+        let action_row = ActionRowBuilder::new()
+            .set_components([FaqBrowserMenu::model(Some(ctx.clone()))?])
+            .build()
+            .context("build action row")?;
+
+        ctx.responder()?
+            .reply(
+                InteractionResponseDataBuilder::new()
+                    .content(ctx.guild_message("commands.faq-browser.content", &[]))
+                    .components([action_row])
+                    .build(),
+            )
+            .await
+            .context("send faq browser")?;
+
+        Ok(())
+    }
+}
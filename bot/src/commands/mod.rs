@@ -1,26 +1,73 @@
 use async_trait::async_trait;
-use twilight_model::application::command::Command;
+use builders::command_option::CommandOptionBuilder;
+use twilight_model::application::command::{Command, CommandOptionType, CommandType};
 use twilight_model::application::interaction::Interaction;
+use twilight_util::builder::command::CommandBuilder;
+
+use crate::access::Access;
+use crate::cooldown::Cooldown;
+use crate::hooks::HookContext;
+use crate::i18n;
+use crate::responder::{auto_defer, Defer};
 
 mod config;
 mod devforum_self_role;
+mod devforum_verify;
 mod faq;
+mod faq_browser;
+pub(crate) mod sync;
 
 /// Get all application command models.
 pub(crate) fn models(ctx: crate::Context) -> anyhow::Result<Vec<Command>> {
     Ok(vec![
         devforum_self_role::DevForumSelfRole::model(None)?,
+        devforum_verify::DevForumVerify::model(None)?,
         config::Config::model(None)?,
         faq::Faq::model(Some(ctx))?,
+        faq_browser::FaqBrowser::model(None)?,
     ])
 }
 
+/// Creates the command with the given name, its description and localizations
+/// are the messages at `commands.<name>` in the catalogues.
+fn command(name: &str, kind: CommandType) -> CommandBuilder {
+    let key = format!("commands.{name}");
+    CommandBuilder::new(name, description(&key), kind)
+        .name_localizations(i18n::localizations(&format!("{key}.name")))
+        .description_localizations(i18n::localizations(&format!("{key}.description")))
+}
+
+/// Creates the option with the given name, its description and localizations
+/// are the messages at the given key in the catalogues.
+fn option(name: &str, key: &str, kind: CommandOptionType) -> CommandOptionBuilder {
+    CommandOptionBuilder::new(name, &description(key), kind)
+        .name_localizations(i18n::localizations(&format!("{key}.name")))
+        .description_localizations(i18n::localizations(&format!("{key}.description")))
+}
+
+/// Returns the description at the given key in the reference catalogue.
+fn description(key: &str) -> String {
+    i18n::message(&i18n::FALLBACK, &format!("{key}.description"), &[])
+}
+
 /// Trait for implementing application commands.
 #[async_trait]
 pub(crate) trait CommandHandler: Send {
     fn model(ctx: Option<crate::Context>) -> anyhow::Result<Command>
     where
         Self: Sized;
+    /// The requirements the member must meet to use the interaction.
+    fn access(&self) -> Access {
+        Access::new()
+    }
+    /// The cooldown applied between uses of the interaction.
+    fn cooldown(&self) -> Option<Cooldown> {
+        None
+    }
+    /// The visibility of the response if the interaction is deferred automatically.
+    fn defer(&self) -> Defer {
+        Defer::Public
+    }
     async fn exec(&self, ctx: crate::Context) -> anyhow::Result<()>;
 }
 
@@ -31,9 +78,41 @@ pub(crate) async fn handle_command(
 ) -> anyhow::Result<()> {
     let handler: Box<dyn CommandHandler> = match cmd_name {
         "devforum-self-role" => Box::new(devforum_self_role::DevForumSelfRole { cmd }),
+        "devforum-verify" => Box::new(devforum_verify::DevForumVerify { cmd }),
         "config" => Box::new(config::Config { cmd }),
         "faq" => Box::new(faq::Faq { cmd }),
+        "faq-browser" => Box::new(faq_browser::FaqBrowser { cmd }),
         unknown => anyhow::bail!("unknown command name: {}", unknown),
     };
-    handler.exec(ctx).await
+    let hook_ctx = HookContext::new(cmd, cmd_name, handler.access(), handler.cooldown());
+    let responder = ctx.responder()?.clone();
+    let hooks = ctx.hooks.clone();
+    let exec = auto_defer(&responder, handler.defer(), handler.exec(ctx.clone()));
+    hooks.run(&hook_ctx, &ctx, exec).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Simulation;
+
+    #[tokio::test]
+    async fn models_are_localized() {
+        let sim = Simulation::new().await;
+        let models = models(sim.ctx.clone()).unwrap();
+        let faq = models.iter().find(|cmd| cmd.name == "faq").unwrap();
+
+        assert_eq!(
+            faq.description,
+            "Send quick responses to common questions/queries."
+        );
+        assert_eq!(
+            faq.description_localizations.as_ref().unwrap()["fr"],
+            "Envoyer des réponses rapides aux questions fréquentes."
+        );
+        assert_eq!(
+            faq.options[0].name_localizations.as_ref().unwrap()["fr"],
+            "question"
+        );
+    }
 }
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use twilight_http::request::Request;
+use twilight_http::routing::Route;
+use twilight_http::Client as HttpClient;
+use twilight_model::application::command::{Command, CommandOption, CommandType};
+use twilight_model::id::marker::ApplicationMarker;
+use twilight_model::id::Id;
+
+/// Changes required to bring the published commands in line with the local models.
+#[derive(Debug, Default)]
+pub(crate) struct CommandDiff {
+    /// Commands that only exist locally.
+    pub(crate) create: Vec<Command>,
+    /// Published commands that differ from their local model,
+    /// paired with the local model that should replace them.
+    pub(crate) update: Vec<(Command, Command)>,
+    /// Published commands that no longer have a local model.
+    pub(crate) delete: Vec<Command>,
+    /// The number of published commands that are already up to date.
+    pub(crate) unchanged: usize,
+}
+
+impl CommandDiff {
+    /// Compares the published commands against the local models.
+    ///
+    /// Commands are matched by their name and type, then compared
+    /// after stripping fields that are assigned by Discord.
+    pub(crate) fn new(published: Vec<Command>, models: Vec<Command>) -> Self {
+        let mut diff = CommandDiff::default();
+        let mut published: HashMap<(String, CommandType), Command> = published
+            .into_iter()
+            .map(|cmd| ((cmd.name.clone(), cmd.kind), cmd))
+            .collect();
+
+        for model in models {
+            match published.remove(&(model.name.clone(), model.kind)) {
+                Some(existing) if normalize(existing.clone()) == normalize(model.clone()) => {
+                    diff.unchanged += 1;
+                },
+                Some(existing) => diff.update.push((existing, model)),
+                None => diff.create.push(model),
+            }
+        }
+
+        diff.delete = published.into_values().collect();
+        diff
+    }
+
+    /// Whether the published commands are already up to date.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.create.is_empty() && self.update.is_empty() && self.delete.is_empty()
+    }
+}
+
+/// Publishes the given command models, only creating, editing or deleting
+/// the commands that differ from what is currently published.
+pub(crate) async fn sync_global_commands(
+    http: &HttpClient,
+    application_id: Id<ApplicationMarker>,
+    models: Vec<Command>,
+) -> anyhow::Result<CommandDiff> {
+    let interaction = http.interaction(application_id);
+    let published = crate::metrics::discord(
+        "global_commands",
+        interaction.global_commands().with_localizations(true),
+    )
+    .await
+    .context("fetch global commands")?
+    .models()
+    .await
+    .context("get global commands")?;
+
+    let diff = CommandDiff::new(published, models);
+
+    for model in &diff.create {
+        // The typed builders do not support every command field (e.g. contexts),
+        // so the full model is sent as the request body instead.
+        let request = Request::builder(&Route::CreateGlobalCommand {
+            application_id: application_id.get(),
+        })
+        .json(model)
+        .build()
+        .context("build create global command request")?;
+        crate::metrics::discord("create_global_command", http.request::<Command>(request))
+            .await
+            .with_context(|| format!("create global command: {}", model.name))?;
+        tracing::info!(command = model.name, "created global command");
+    }
+
+    for (existing, model) in &diff.update {
+        let command_id = existing.id.context("get published command id")?;
+        let request = Request::builder(&Route::UpdateGlobalCommand {
+            application_id: application_id.get(),
+            command_id: command_id.get(),
+        })
+        .json(model)
+        .build()
+        .context("build update global command request")?;
+        crate::metrics::discord("update_global_command", http.request::<Command>(request))
+            .await
+            .with_context(|| format!("update global command: {}", model.name))?;
+        tracing::info!(command = model.name, "updated global command");
+    }
+
+    for existing in &diff.delete {
+        let command_id = existing.id.context("get published command id")?;
+        crate::metrics::discord(
+            "delete_global_command",
+            interaction.delete_global_command(command_id),
+        )
+        .await
+        .with_context(|| format!("delete global command: {}", existing.name))?;
+        tracing::info!(command = existing.name, "deleted global command");
+    }
+
+    Ok(diff)
+}
+
+/// Strips the fields assigned by Discord and collapses values that
+/// Discord treats as equivalent (e.g. `Some(false)` and `None`).
+fn normalize(mut cmd: Command) -> Command {
+    cmd.application_id = None;
+    cmd.guild_id = None;
+    cmd.id = None;
+    cmd.version = Id::new(1);
+    // Deprecated in favour of `contexts`.
+    #[allow(deprecated)]
+    {
+        cmd.dm_permission = None;
+    }
+    cmd.nsfw = cmd.nsfw.filter(|nsfw| *nsfw);
+    cmd.name_localizations = cmd.name_localizations.filter(|map| !map.is_empty());
+    cmd.description_localizations = cmd.description_localizations.filter(|map| !map.is_empty());
+    cmd.options = cmd.options.into_iter().map(normalize_option).collect();
+    cmd
+}
+
+/// Normalizes a command option and its nested options.
+/// See [`normalize`].
+fn normalize_option(mut opt: CommandOption) -> CommandOption {
+    opt.autocomplete = opt.autocomplete.filter(|autocomplete| *autocomplete);
+    opt.required = opt.required.filter(|required| *required);
+    opt.channel_types = opt.channel_types.filter(|types| !types.is_empty());
+    opt.name_localizations = opt.name_localizations.filter(|map| !map.is_empty());
+    opt.description_localizations = opt.description_localizations.filter(|map| !map.is_empty());
+    opt.choices = opt
+        .choices
+        .filter(|choices| !choices.is_empty())
+        .map(|choices| {
+            choices
+                .into_iter()
+                .map(|mut choice| {
+                    choice.name_localizations =
+                        choice.name_localizations.filter(|map| !map.is_empty());
+                    choice
+                })
+                .collect()
+        });
+    opt.options = opt
+        .options
+        .filter(|options| !options.is_empty())
+        .map(|options| options.into_iter().map(normalize_option).collect());
+    opt
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_model::application::command::CommandOptionType;
+    use twilight_util::builder::command::{CommandBuilder, StringBuilder};
+
+    use super::*;
+
+    fn command(name: &str, description: &str) -> Command {
+        CommandBuilder::new(name, description, CommandType::ChatInput)
+            .option(StringBuilder::new("query", "The query.").required(false))
+            .build()
+    }
+
+    /// Returns the command as it would be returned by Discord.
+    fn published(mut cmd: Command, id: u64) -> Command {
+        cmd.id = Some(Id::new(id));
+        cmd.application_id = Some(Id::new(1));
+        cmd.version = Id::new(id + 100);
+        #[allow(deprecated)]
+        {
+            cmd.dm_permission = Some(true);
+        }
+        cmd.nsfw = Some(false);
+        cmd.options[0].required = None;
+        cmd
+    }
+
+    #[test]
+    fn unchanged() {
+        let diff = CommandDiff::new(vec![published(command("faq", "FAQ"), 2)], vec![command(
+            "faq", "FAQ",
+        )]);
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.unchanged, 1);
+    }
+
+    #[test]
+    fn create_update_delete() {
+        let diff = CommandDiff::new(
+            vec![
+                published(command("faq", "FAQ"), 2),
+                published(command("removed", "Removed"), 3),
+            ],
+            vec![command("faq", "Changed"), command("added", "Added")],
+        );
+
+        assert_eq!(diff.create.len(), 1);
+        assert_eq!(diff.create[0].name, "added");
+        assert_eq!(diff.update.len(), 1);
+        assert_eq!(diff.update[0].0.id, Some(Id::new(2)));
+        assert_eq!(diff.update[0].1.description, "Changed");
+        assert_eq!(diff.delete.len(), 1);
+        assert_eq!(diff.delete[0].name, "removed");
+        assert_eq!(diff.unchanged, 0);
+    }
+
+    #[test]
+    fn option_changes() {
+        let mut model = command("faq", "FAQ");
+        model.options[0].kind = CommandOptionType::User;
+        let diff = CommandDiff::new(vec![published(command("faq", "FAQ"), 2)], vec![model]);
+
+        assert_eq!(diff.update.len(), 1);
+    }
+}
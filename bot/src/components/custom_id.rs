@@ -0,0 +1,166 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::Context;
+
+/// The maximum length of a custom ID, as enforced by the API.
+const CUSTOM_ID_LENGTH: usize = 100;
+/// Separates the prefix and each argument.
+const SEPARATOR: char = ':';
+/// Starts an escape sequence in an argument.
+const ESCAPE: char = '%';
+
+/// A component custom ID made of a prefix, used to route the interaction
+/// to its handler, followed by arguments encoded in the ID.
+///
+/// Encoded as `prefix:arg1:arg2`, where [`SEPARATOR`] and [`ESCAPE`]
+/// characters in arguments are percent-encoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CustomId {
+    prefix: String,
+    args: Vec<String>,
+}
+
+impl CustomId {
+    /// Create a new custom ID with the given prefix and no arguments.
+    pub(crate) fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            args: Vec::new(),
+        }
+    }
+
+    /// Add an argument to the custom ID.
+    pub(crate) fn arg(mut self, arg: impl Display) -> Self {
+        self.args.push(arg.to_string());
+        self
+    }
+
+    /// Returns the prefix used to route the interaction to its handler.
+    pub(crate) fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Parses the argument at the given index.
+    pub(crate) fn parse_arg<T>(&self, index: usize) -> anyhow::Result<T>
+    where
+        T: FromStr,
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        self.args
+            .get(index)
+            .with_context(|| format!("missing custom id argument at index {index}"))?
+            .parse()
+            .with_context(|| format!("parse custom id argument at index {index}"))
+    }
+
+    /// Encodes the custom ID, failing if it exceeds the maximum length
+    /// or if the prefix contains reserved characters.
+    pub(crate) fn encode(&self) -> anyhow::Result<String> {
+        if self.prefix.is_empty() || self.prefix.contains([SEPARATOR, ESCAPE]) {
+            anyhow::bail!("invalid custom id prefix: {:?}", self.prefix);
+        }
+
+        let mut encoded = self.prefix.clone();
+        for arg in &self.args {
+            encoded.push(SEPARATOR);
+            encoded.push_str(&escape(arg));
+        }
+
+        let length = encoded.chars().count();
+        if length > CUSTOM_ID_LENGTH {
+            anyhow::bail!(
+                "custom id is {length} characters long, the maximum is {CUSTOM_ID_LENGTH}: {encoded}"
+            );
+        }
+
+        Ok(encoded)
+    }
+}
+
+impl FromStr for CustomId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(SEPARATOR);
+        let prefix = parts.next().unwrap_or_default();
+        if prefix.is_empty() {
+            anyhow::bail!("missing custom id prefix: {s}");
+        }
+
+        Ok(Self {
+            prefix: prefix.to_string(),
+            args: parts.map(unescape).collect::<anyhow::Result<_>>()?,
+        })
+    }
+}
+
+/// Percent-encodes the reserved characters of an argument.
+fn escape(arg: &str) -> String {
+    arg.replace(ESCAPE, "%25").replace(SEPARATOR, "%3A")
+}
+
+/// Decodes the percent-encoded reserved characters of an argument.
+fn unescape(arg: &str) -> anyhow::Result<String> {
+    let mut decoded = String::with_capacity(arg.len());
+    let mut chars = arg.chars();
+
+    while let Some(c) = chars.next() {
+        if c != ESCAPE {
+            decoded.push(c);
+            continue;
+        }
+
+        let sequence: String = chars.by_ref().take(2).collect();
+        match sequence.as_str() {
+            "25" => decoded.push(ESCAPE),
+            "3A" => decoded.push(SEPARATOR),
+            _ => anyhow::bail!("invalid escape sequence in custom id argument: {arg}"),
+        }
+    }
+
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let custom_id = CustomId::new("faq").arg("a:b%c").arg(42);
+        let encoded = custom_id.encode().expect("failed to encode custom id");
+
+        assert_eq!(encoded, "faq:a%3Ab%25c:42");
+
+        let decoded = CustomId::from_str(&encoded).expect("failed to decode custom id");
+
+        assert_eq!(decoded, custom_id);
+        assert_eq!(decoded.prefix(), "faq");
+        assert_eq!(decoded.parse_arg::<String>(0).unwrap(), "a:b%c");
+        assert_eq!(decoded.parse_arg::<u64>(1).unwrap(), 42);
+        assert!(decoded.parse_arg::<u64>(2).is_err());
+    }
+
+    #[test]
+    fn without_args() {
+        let decoded = CustomId::from_str("verify-devforum-rank").unwrap();
+
+        assert_eq!(decoded.prefix(), "verify-devforum-rank");
+        assert_eq!(decoded.encode().unwrap(), "verify-devforum-rank");
+    }
+
+    #[test]
+    fn length() {
+        assert!(CustomId::new("a").arg("b".repeat(98)).encode().is_ok());
+        assert!(CustomId::new("a").arg("b".repeat(99)).encode().is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(CustomId::new("a:b").encode().is_err());
+        assert!(CustomId::new("").encode().is_err());
+        assert!(CustomId::from_str("").is_err());
+        assert!(CustomId::from_str("a:%zz").is_err());
+    }
+}
@@ -0,0 +1,195 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use builders::component::{ActionRowBuilder, ButtonBuilder, SelectMenuBuilder};
+use twilight_model::channel::message::component::{ButtonStyle, SelectMenuType};
+use twilight_model::channel::message::Component;
+use twilight_model::http::interaction::InteractionResponseData;
+
+use crate::components::custom_id::CustomId;
+use crate::components::{ComponentHandler, ComponentInteraction};
+use crate::config::validate::ACTION_ROW_COUNT;
+use crate::error::InteractionError;
+use crate::responder::Defer;
+
+/// The custom ID prefix of the select menu and of the buttons,
+/// which have the position of the FAQ option they show as argument.
+pub(crate) const PREFIX: &str = "faq-browser";
+
+pub(crate) struct FaqBrowser<'a> {
+    pub(crate) component: ComponentInteraction<'a>,
+}
+
+impl FaqBrowser<'_> {
+    /// Builds the select menu listing the FAQ options,
+    /// marking the option with the given value as selected.
+    pub(crate) fn select_menu(
+        ctx: &crate::Context,
+        selected: Option<&str>,
+    ) -> anyhow::Result<Component> {
+        Ok(
+            SelectMenuBuilder::new(CustomId::new(PREFIX).encode()?, SelectMenuType::Text)
+                .set_options(ctx.cfg().faq_select_options(selected))
+                .placeholder(ctx.guild_message("components.faq-browser.placeholder", &[]))
+                .validate()
+                .context("validate faq browser select menu")?
+                .build(),
+        )
+    }
+
+    /// Builds the buttons showing the FAQ options before and after the one at the given
+    /// position, or [`None`] if there's no other option.
+    fn navigation(ctx: &crate::Context, index: usize) -> anyhow::Result<Option<Component>> {
+        let mut buttons = Vec::new();
+        if index > 0 {
+            buttons.push(Self::button(ctx, index - 1, "previous")?);
+        }
+        if index + 1 < ctx.cfg().faq_option_count() {
+            buttons.push(Self::button(ctx, index + 1, "next")?);
+        }
+
+        if buttons.is_empty() {
+            return Ok(None);
+        }
+        ActionRowBuilder::new()
+            .set_components(buttons)
+            .build()
+            .map(Some)
+    }
+
+    /// Builds the button showing the FAQ option at the given position.
+    fn button(ctx: &crate::Context, index: usize, label: &str) -> anyhow::Result<Component> {
+        let label = ctx.guild_message(&format!("components.faq-browser.{label}"), &[]);
+        ButtonBuilder::new(
+            CustomId::new(PREFIX).arg(index).encode()?,
+            ButtonStyle::Secondary,
+        )
+        .label(label)
+        .build()
+    }
+
+    /// Returns the value of the chosen FAQ option, from the select menu or a button.
+    fn chosen_value(&self, ctx: &crate::Context) -> anyhow::Result<String> {
+        if let Some(values) = &self.component.values {
+            return values
+                .strings()?
+                .first()
+                .cloned()
+                .context("missing selected faq option");
+        }
+
+        let index: usize = self.component.custom_id.parse_arg(0)?;
+        match ctx.cfg().faq_option_value(index) {
+            Some(value) => Ok(value.to_string()),
+            // The option was removed since the message was sent.
+            None => Err(InteractionError::user(
+                ctx.message("commands.faq.unknown_option", &[("value", &index)]),
+            )
+            .into()),
+        }
+    }
+}
+
+#[async_trait]
+impl ComponentHandler for FaqBrowser<'_> {
+    fn model(ctx: Option<crate::Context>) -> anyhow::Result<Component> {
+        let ctx = ctx.expect("ctx is required");
+        Self::select_menu(&ctx, None)
+    }
+
+    fn defer(&self) -> Defer {
+        // The answer replaces the message of the select menu
+        Defer::Update
+    }
+
+    async fn exec(&self, ctx: crate::Context) -> anyhow::Result<()> {
+        let value = self.chosen_value(&ctx)?;
+        let cfg = ctx.cfg();
+        let (Some(index), Some(answer)) = (
+            cfg.faq_option_index(&value),
+            cfg.faq_option_response(&value),
+        ) else {
+            return Err(InteractionError::user(
+                ctx.message("commands.faq.unknown_option", &[("value", &value)]),
+            )
+            .into());
+        };
+
+        // Keep the select menu below the components of the answer,
+        // followed by the buttons if the answer leaves a row for them
+        let mut components = answer.components.unwrap_or_default();
+        components.push(
+            ActionRowBuilder::new()
+                .add_component(Self::select_menu(&ctx, Some(&value))?)
+                .build()
+                .context("build action row")?,
+        );
+        if components.len() < ACTION_ROW_COUNT {
+            components.extend(Self::navigation(&ctx, index)?);
+        }
+
+        // Replace the displayed answer, clearing the fields
+        // that the new answer doesn't set
+        ctx.responder()?
+            .update_message(InteractionResponseData {
+                content: Some(answer.content.unwrap_or_default()),
+                embeds: Some(answer.embeds.unwrap_or_default()),
+                components: Some(components),
+                ..Default::default()
+            })
+            .await
+            .context("update faq browser message")?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Simulation;
+
+    #[tokio::test]
+    async fn shows_selected_answer() {
+        let sim = Simulation::new().await;
+        let custom_id = CustomId::new(PREFIX).encode().unwrap();
+
+        sim.run(&sim.select(&custom_id, &["bug-report"]))
+            .await
+            .unwrap();
+
+        let responses = sim.responses();
+        // Updates the message the select menu is attached to
+        assert_eq!(responses[0]["type"], 7);
+        assert_eq!(responses[0]["data"]["embeds"][0]["title"], "Reporting bugs");
+        assert_eq!(
+            responses[0]["data"]["components"][0]["components"][0]["options"][1]["default"],
+            true
+        );
+        // The last option only has a button to the previous one
+        let buttons = &responses[0]["data"]["components"][1]["components"];
+        assert_eq!(buttons.as_array().unwrap().len(), 1);
+        assert_eq!(buttons[0]["custom_id"], "faq-browser:0");
+    }
+
+    #[tokio::test]
+    async fn navigates_with_buttons() {
+        let sim = Simulation::new().await;
+        let custom_id = CustomId::new(PREFIX).arg(1).encode().unwrap();
+
+        sim.run(&sim.button(&custom_id)).await.unwrap();
+
+        let responses = sim.responses();
+        assert_eq!(responses[0]["type"], 7);
+        assert_eq!(responses[0]["data"]["embeds"][0]["title"], "Reporting bugs");
+
+        // Options removed since the message was sent are reported to the user
+        let sim = Simulation::new().await;
+        let custom_id = CustomId::new(PREFIX).arg(9).encode().unwrap();
+
+        sim.run(&sim.button(&custom_id)).await.unwrap();
+        assert!(sim
+            .last_content()
+            .unwrap()
+            .starts_with("Unknown FAQ option: `9`"));
+    }
+}
@@ -1,27 +1,78 @@
+use std::str::FromStr;
+
 use async_trait::async_trait;
+use twilight_model::application::interaction::message_component::MessageComponentInteractionData;
 use twilight_model::application::interaction::Interaction;
 use twilight_model::channel::message::Component;
 
+use crate::access::Access;
+use crate::components::custom_id::CustomId;
+use crate::components::select_values::SelectValues;
+use crate::cooldown::Cooldown;
+use crate::hooks::HookContext;
+use crate::responder::{auto_defer, Defer};
+
+pub(crate) mod custom_id;
+pub(crate) mod faq_browser;
+pub(crate) mod select_values;
 pub(crate) mod verify_devforum_rank;
 
 /// Trait for implementing message components.
 /// See the [`Component`] enum for supported components.
+///
+/// Components are routed to their handler by the prefix of their [`CustomId`],
+/// the handler receives the parsed custom ID to access its arguments, and
+/// select menu handlers receive the chosen [`SelectValues`].
 #[async_trait]
 pub(crate) trait ComponentHandler: Send {
-    fn model() -> anyhow::Result<Component>
+    fn model(ctx: Option<crate::Context>) -> anyhow::Result<Component>
     where
         Self: Sized;
+    /// The requirements the member must meet to use the interaction.
+    fn access(&self) -> Access {
+        Access::new()
+    }
+    /// The cooldown applied between uses of the interaction.
+    fn cooldown(&self) -> Option<Cooldown> {
+        None
+    }
+    /// How the interaction is deferred if its handler is slow to respond.
+    fn defer(&self) -> Defer {
+        Defer::Public
+    }
     async fn exec(&self, ctx: crate::Context) -> anyhow::Result<()>;
 }
 
+/// A message component interaction passed to its handler.
+pub(crate) struct ComponentInteraction<'a> {
+    pub(crate) cmd: &'a Interaction,
+    /// The parsed custom ID of the component.
+    pub(crate) custom_id: CustomId,
+    /// The chosen values if the component is a select menu, [`None`] otherwise.
+    pub(crate) values: Option<SelectValues>,
+}
+
 pub(crate) async fn handle_component(
     cmd: &Interaction,
-    custom_id: &str,
+    data: &MessageComponentInteractionData,
     ctx: crate::Context,
 ) -> anyhow::Result<()> {
-    let handler: Box<dyn ComponentHandler> = match custom_id {
-        "verify-devforum-rank" => Box::new(verify_devforum_rank::VerifyDevForumRank { cmd }),
-        unknown => anyhow::bail!("unknown component custom id: {}", unknown),
+    let component = ComponentInteraction {
+        cmd,
+        custom_id: CustomId::from_str(&data.custom_id)?,
+        values: SelectValues::from_data(data)?,
+    };
+    let prefix = component.custom_id.prefix().to_string();
+    let handler: Box<dyn ComponentHandler> = match prefix.as_str() {
+        verify_devforum_rank::PREFIX => {
+            Box::new(verify_devforum_rank::VerifyDevForumRank { component })
+        },
+        faq_browser::PREFIX => Box::new(faq_browser::FaqBrowser { component }),
+        unknown => anyhow::bail!("unknown component custom id prefix: {}", unknown),
     };
-    handler.exec(ctx).await
+    let hook_ctx = HookContext::new(cmd, &prefix, handler.access(), handler.cooldown());
+    let responder = ctx.responder()?.clone();
+    let hooks = ctx.hooks.clone();
+    let exec = auto_defer(&responder, handler.defer(), handler.exec(ctx.clone()));
+    hooks.run(&hook_ctx, &ctx, exec).await
 }
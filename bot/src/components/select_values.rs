@@ -0,0 +1,99 @@
+use std::str::FromStr;
+
+use anyhow::Context;
+use twilight_model::application::interaction::message_component::MessageComponentInteractionData;
+use twilight_model::channel::message::component::ComponentType;
+use twilight_model::id::marker::{ChannelMarker, GenericMarker, RoleMarker, UserMarker};
+use twilight_model::id::Id;
+
+/// The values chosen in a select menu, typed by the kind of select menu.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum SelectValues {
+    /// The values of the chosen options of a text select menu.
+    String(Vec<String>),
+    /// The chosen users of a user select menu.
+    User(Vec<Id<UserMarker>>),
+    /// The chosen roles of a role select menu.
+    Role(Vec<Id<RoleMarker>>),
+    /// The chosen users and roles of a mentionable select menu.
+    Mentionable(Vec<Id<GenericMarker>>),
+    /// The chosen channels of a channel select menu.
+    Channel(Vec<Id<ChannelMarker>>),
+}
+
+impl SelectValues {
+    /// Parses the values of a select menu interaction.
+    ///
+    /// Returns [`None`] if the component isn't a select menu.
+    pub(crate) fn from_data(
+        data: &MessageComponentInteractionData,
+    ) -> anyhow::Result<Option<Self>> {
+        let values = match data.component_type {
+            ComponentType::TextSelectMenu => SelectValues::String(data.values.clone()),
+            ComponentType::UserSelectMenu => SelectValues::User(parse_ids(&data.values)?),
+            ComponentType::RoleSelectMenu => SelectValues::Role(parse_ids(&data.values)?),
+            ComponentType::MentionableSelectMenu => {
+                SelectValues::Mentionable(parse_ids(&data.values)?)
+            },
+            ComponentType::ChannelSelectMenu => SelectValues::Channel(parse_ids(&data.values)?),
+            _ => return Ok(None),
+        };
+        Ok(Some(values))
+    }
+
+    /// Returns the values of a text select menu.
+    pub(crate) fn strings(&self) -> anyhow::Result<&[String]> {
+        match self {
+            SelectValues::String(values) => Ok(values),
+            _ => anyhow::bail!("expected text select menu values"),
+        }
+    }
+}
+
+/// Parses the IDs chosen in an auto-populated select menu.
+fn parse_ids<T>(values: &[String]) -> anyhow::Result<Vec<Id<T>>> {
+    values
+        .iter()
+        .map(|value| Id::from_str(value).with_context(|| format!("parse select menu id: {value}")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(component_type: ComponentType, values: &[&str]) -> MessageComponentInteractionData {
+        MessageComponentInteractionData {
+            custom_id: "custom_id".to_string(),
+            component_type,
+            resolved: None,
+            values: values.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn typed_values() {
+        let strings = SelectValues::from_data(&data(ComponentType::TextSelectMenu, &["a", "b"]))
+            .unwrap()
+            .unwrap();
+        let roles = SelectValues::from_data(&data(ComponentType::RoleSelectMenu, &["1", "2"]))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(strings.strings().unwrap(), ["a", "b"]);
+        assert_eq!(roles, SelectValues::Role(vec![Id::new(1), Id::new(2)]));
+        assert!(roles.strings().is_err());
+    }
+
+    #[test]
+    fn not_a_select_menu() {
+        assert!(SelectValues::from_data(&data(ComponentType::Button, &[]))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn invalid_id() {
+        assert!(SelectValues::from_data(&data(ComponentType::UserSelectMenu, &["user"])).is_err());
+    }
+}
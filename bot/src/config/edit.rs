@@ -0,0 +1,461 @@
+use anyhow::Context;
+use builders::locale::Locale;
+use toml_edit::{Array, DocumentMut, Item, TableLike};
+use twilight_model::id::marker::RoleMarker;
+use twilight_model::id::Id;
+
+use super::format::ConfigFormat;
+use super::validate::{self, ConfigError};
+use super::Config;
+use crate::i18n;
+
+/// A setting of the config that can be read and changed while the bot runs.
+pub(crate) struct Setting {
+    /// The path of the setting in the config file.
+    pub(crate) path: &'static str,
+    /// Returns the value of the setting as inline YAML.
+    get: fn(&Config) -> String,
+    /// Returns the roles the setting is set to.
+    roles: fn(&Config) -> Vec<Id<RoleMarker>>,
+    /// Parses the value and sets it.
+    set: fn(&mut Config, &str) -> Result<(), EditError>,
+}
+
+/// The reason a setting can't be changed to a value, caused by the user.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum EditError {
+    /// The value isn't a role ID or mention.
+    InvalidRole(String),
+    /// The config breaks the rules checked at startup.
+    InvalidConfig(Vec<ConfigError>),
+}
+
+impl EditError {
+    /// Returns the message telling the user why the setting wasn't changed.
+    pub(crate) fn message(&self, locale: &Locale) -> String {
+        match self {
+            EditError::InvalidRole(value) => {
+                i18n::message(locale, "commands.config.set.invalid_role", &[(
+                    "value", value,
+                )])
+            },
+            EditError::InvalidConfig(errors) => {
+                let errors: Vec<String> = errors
+                    .iter()
+                    .map(|error| format!("- {}", error.message(locale)))
+                    .collect();
+                i18n::message(locale, "commands.config.set.invalid_config", &[(
+                    "errors",
+                    &errors.join("\n"),
+                )])
+            },
+        }
+    }
+}
+
+impl Setting {
+    /// Returns the value of the setting as inline YAML.
+    pub(crate) fn get(&self, cfg: &Config) -> String {
+        (self.get)(cfg)
+    }
+
+    /// Returns the roles the setting is set to.
+    pub(crate) fn roles(&self, cfg: &Config) -> Vec<Id<RoleMarker>> {
+        (self.roles)(cfg)
+    }
+}
+
+/// The settings that can be changed while the bot runs, which are applied live.
+pub(crate) const SETTINGS: &[Setting] = &[
+    Setting {
+        path: "roles.devforum_member",
+        get: |cfg| role(cfg.roles.devforum_member),
+        roles: |cfg| vec![cfg.roles.devforum_member],
+        set: |cfg, value| {
+            cfg.roles.devforum_member = parse_role(value)?;
+            Ok(())
+        },
+    },
+    Setting {
+        path: "roles.devforum_regular",
+        get: |cfg| role(cfg.roles.devforum_regular),
+        roles: |cfg| vec![cfg.roles.devforum_regular],
+        set: |cfg, value| {
+            cfg.roles.devforum_regular = parse_role(value)?;
+            Ok(())
+        },
+    },
+    Setting {
+        path: "roles.roblox_verified",
+        get: |cfg| {
+            cfg.roles
+                .roblox_verified
+                .map_or_else(|| "null".to_string(), role)
+        },
+        roles: |cfg| cfg.roles.roblox_verified.into_iter().collect(),
+        set: |cfg, value| {
+            cfg.roles.roblox_verified = match value {
+                "none" => None,
+                value => Some(parse_role(value)?),
+            };
+            Ok(())
+        },
+    },
+    Setting {
+        path: "roles.cooldown_bypass",
+        get: |cfg| {
+            let roles: Vec<String> = cfg
+                .roles
+                .cooldown_bypass
+                .iter()
+                .map(|id| role(*id))
+                .collect();
+            format!("[{}]", roles.join(", "))
+        },
+        roles: |cfg| cfg.roles.cooldown_bypass.clone(),
+        set: |cfg, value| {
+            cfg.roles.cooldown_bypass = match value {
+                "none" => Vec::new(),
+                value => value
+                    .split([',', ' '])
+                    .filter(|id| !id.is_empty())
+                    .map(parse_role)
+                    .collect::<Result<_, _>>()?,
+            };
+            Ok(())
+        },
+    },
+];
+
+/// Returns the setting at the given path.
+pub(crate) fn setting(path: &str) -> Option<&'static Setting> {
+    SETTINGS.iter().find(|setting| setting.path == path)
+}
+
+/// Formats a role ID as a YAML string, as IDs are written in the config file.
+fn role(id: Id<RoleMarker>) -> String {
+    format!("\"{id}\"")
+}
+
+/// Parses a role ID or mention.
+fn parse_role(value: &str) -> Result<Id<RoleMarker>, EditError> {
+    let id = value
+        .strip_prefix("<@&")
+        .and_then(|id| id.strip_suffix('>'))
+        .unwrap_or(value);
+    id.parse()
+        .map_err(|_| EditError::InvalidRole(value.to_string()))
+}
+
+/// Returns a copy of the config with the setting changed to the given value.
+///
+/// Fails if the value can't be parsed, or if the config breaks any rule checked at startup.
+pub(crate) fn apply(cfg: &Config, setting: &Setting, value: &str) -> Result<Config, EditError> {
+    let mut cfg = cfg.clone();
+    (setting.set)(&mut cfg, value.trim())?;
+
+    let errors = validate::validate(&cfg);
+    if !errors.is_empty() {
+        return Err(EditError::InvalidConfig(errors));
+    }
+
+    Ok(cfg)
+}
+
+/// Returns the source of a config file with the value at the path replaced,
+/// preserving its comments and formatting where possible.
+///
+/// The value is given as inline YAML, as returned by [`Setting::get`].
+pub(crate) fn edit_source(
+    format: ConfigFormat,
+    source: &str,
+    path: &str,
+    value: &str,
+) -> anyhow::Result<String> {
+    let parsed: serde_yaml::Value = serde_yaml::from_str(value).context("parse value")?;
+    match format {
+        ConfigFormat::Yaml => edit_yaml(source, path, value, parsed),
+        ConfigFormat::Toml => edit_toml(source, path, &parsed),
+        ConfigFormat::Json => {
+            let mut document = format.parse(source).context("parse config file")?;
+            *value_mut(&mut document, path)? = parsed;
+            let json = serde_json::to_string_pretty(&document).context("serialize config file")?;
+            Ok(json + "\n")
+        },
+    }
+}
+
+/// Returns the YAML source with the value at the path replaced.
+///
+/// The line of the value is edited in place to preserve the comments and formatting,
+/// unless the value is in an inline collection, in which case the document is rewritten.
+fn edit_yaml(
+    source: &str,
+    path: &str,
+    value: &str,
+    parsed: serde_yaml::Value,
+) -> anyhow::Result<String> {
+    let mut expected: serde_yaml::Value =
+        serde_yaml::from_str(source).context("parse config file")?;
+    *value_mut(&mut expected, path)? = parsed;
+
+    if let Some(edited) = edit_lines(source, path, value) {
+        if serde_yaml::from_str::<serde_yaml::Value>(&edited).ok() == Some(expected.clone()) {
+            return Ok(edited);
+        }
+    }
+
+    tracing::warn!(path, "rewriting the config file, its comments are lost");
+    serde_yaml::to_string(&expected).context("serialize config file")
+}
+
+/// Returns the TOML source with the value at the path replaced, removing it if it's null.
+///
+/// The document is edited in place, which preserves its comments and formatting.
+fn edit_toml(source: &str, path: &str, value: &serde_yaml::Value) -> anyhow::Result<String> {
+    let mut document: DocumentMut = source.parse().context("parse config file")?;
+    let (parents, key) = match path.rsplit_once('.') {
+        Some((parents, key)) => (Some(parents), key),
+        None => (None, path),
+    };
+
+    let mut table: &mut dyn TableLike = document.as_table_mut();
+    for parent in parents.into_iter().flat_map(|parents| parents.split('.')) {
+        table = table
+            .entry(parent)
+            .or_insert(toml_edit::table())
+            .as_table_like_mut()
+            .with_context(|| format!("expected a table at `{parent}` of `{path}`"))?;
+    }
+
+    match (toml_value(value)?, table.get_mut(key)) {
+        // Keep the comments around the previous value.
+        (Some(value), Some(Item::Value(previous))) => {
+            let decor = previous.decor().clone();
+            *previous = value;
+            *previous.decor_mut() = decor;
+        },
+        (Some(value), _) => {
+            table.insert(key, Item::Value(value));
+        },
+        (None, _) => {
+            table.remove(key);
+        },
+    }
+
+    Ok(document.to_string())
+}
+
+/// Converts a YAML value to TOML, returning [`None`] for null.
+fn toml_value(value: &serde_yaml::Value) -> anyhow::Result<Option<toml_edit::Value>> {
+    Ok(Some(match value {
+        serde_yaml::Value::Null => return Ok(None),
+        serde_yaml::Value::Bool(value) => (*value).into(),
+        serde_yaml::Value::Number(number) => match number.as_i64() {
+            Some(number) => number.into(),
+            None => number.as_f64().context("convert number to TOML")?.into(),
+        },
+        serde_yaml::Value::String(value) => value.as_str().into(),
+        serde_yaml::Value::Sequence(values) => {
+            let mut array = Array::new();
+            for value in values {
+                array.push(toml_value(value)?.context("convert null to TOML")?);
+            }
+            array.into()
+        },
+        serde_yaml::Value::Mapping(_) | serde_yaml::Value::Tagged(_) => {
+            anyhow::bail!("convert {value:?} to TOML")
+        },
+    }))
+}
+
+/// Returns the value at the path in the document, inserting it in its mapping if missing.
+fn value_mut<'a>(
+    document: &'a mut serde_yaml::Value,
+    path: &str,
+) -> anyhow::Result<&'a mut serde_yaml::Value> {
+    path.split('.').try_fold(document, |node, key| {
+        let mapping = node
+            .as_mapping_mut()
+            .with_context(|| format!("expected a mapping at `{key}` of `{path}`"))?;
+        Ok(mapping
+            .entry(serde_yaml::Value::String(key.to_string()))
+            .or_insert(serde_yaml::Value::Null))
+    })
+}
+
+/// Replaces the value at the path by editing the lines of the source,
+/// returning [`None`] if the path or its parent can't be found.
+fn edit_lines(source: &str, path: &str, value: &str) -> Option<String> {
+    let lines = validate::lines(source)?;
+    let mut result: Vec<String> = source.lines().map(ToString::to_string).collect();
+    let (parent, key) = path.rsplit_once('.').unwrap_or(("", path));
+
+    match lines.get(path) {
+        Some(&line) => {
+            let index = line - 1;
+            let text = &result[index];
+            let indent = indentation(text);
+            let comment = comment(text).unwrap_or_default().to_string();
+            result[index] = format!("{}{key}: {value}{comment}", &text[..indent]);
+
+            // Remove the remaining lines of a block value, keeping the comments.
+            let mut next = index + 1;
+            while next < result.len() {
+                let text = result[next].trim_start();
+                let nested = match indentation(&result[next]) {
+                    // Sequences can be at the same indentation as their key.
+                    next_indent if next_indent == indent => text.starts_with("- "),
+                    next_indent => next_indent > indent,
+                };
+                if !text.is_empty() && !text.starts_with('#') && !nested {
+                    break;
+                }
+                if nested && !text.is_empty() && !text.starts_with('#') {
+                    result.remove(next);
+                } else {
+                    next += 1;
+                }
+            }
+        },
+        None => {
+            let index = match parent {
+                "" => result.len(),
+                parent => *lines.get(parent)?,
+            };
+            // Insert the key as the first entry, with the indentation of the other entries.
+            let indent = result[index..]
+                .iter()
+                .find(|text| !text.trim().is_empty() && !text.trim_start().starts_with('#'))
+                .map_or(0, |text| indentation(text));
+            result.insert(index, format!("{}{key}: {value}", " ".repeat(indent)));
+        },
+    }
+
+    let mut edited = result.join("\n");
+    if source.ends_with('\n') {
+        edited.push('\n');
+    }
+    Some(edited)
+}
+
+/// Returns the number of spaces the line is indented by.
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// Returns the trailing comment of a line, with the spaces before it.
+fn comment(line: &str) -> Option<&str> {
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, '#') if line[..i].ends_with([' ', '\t']) => {
+                return Some(&line[line[..i].trim_end().len()..]);
+            },
+            _ => {},
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"# The roles of the server
+roles:
+  devforum_member: "1" # Member
+  devforum_regular: "2"
+  cooldown_bypass:
+    # Moderators
+    - "3"
+    - "4"
+
+faq_options: []
+"#;
+
+    #[test]
+    fn edits_lines_in_place() {
+        let edited =
+            edit_source(ConfigFormat::Yaml, SOURCE, "roles.devforum_member", "\"5\"").unwrap();
+        assert_eq!(
+            edited,
+            SOURCE.replace(r#"devforum_member: "1""#, r#"devforum_member: "5""#)
+        );
+
+        let edited = edit_source(
+            ConfigFormat::Yaml,
+            SOURCE,
+            "roles.cooldown_bypass",
+            "[\"5\"]",
+        )
+        .unwrap();
+        assert!(edited.contains("  cooldown_bypass: [\"5\"]\n    # Moderators\n\nfaq_options"));
+
+        let edited =
+            edit_source(ConfigFormat::Yaml, SOURCE, "roles.roblox_verified", "\"6\"").unwrap();
+        assert!(edited.contains("roles:\n  roblox_verified: \"6\"\n  devforum_member"));
+    }
+
+    #[test]
+    fn rewrites_inline_collections() {
+        let source = "roles: { devforum_member: \"1\", devforum_regular: \"2\" }\n";
+        let edited = edit_source(
+            ConfigFormat::Yaml,
+            source,
+            "roles.devforum_regular",
+            "\"3\"",
+        )
+        .unwrap();
+
+        let document: serde_yaml::Value = serde_yaml::from_str(&edited).unwrap();
+        assert_eq!(document["roles"]["devforum_regular"], "3");
+        assert_eq!(document["roles"]["devforum_member"], "1");
+    }
+
+    #[test]
+    fn edits_toml_in_place() {
+        let source = "[roles]\ndevforum_member = \"1\" # Member\ndevforum_regular = \"2\"\n";
+
+        let edited =
+            edit_source(ConfigFormat::Toml, source, "roles.devforum_member", "\"5\"").unwrap();
+        assert_eq!(edited, source.replace("\"1\"", "\"5\""));
+
+        let edited = edit_source(
+            ConfigFormat::Toml,
+            source,
+            "roles.cooldown_bypass",
+            "[\"6\"]",
+        )
+        .unwrap();
+        assert!(edited.ends_with("cooldown_bypass = [\"6\"]\n"));
+
+        let edited =
+            edit_source(ConfigFormat::Toml, source, "roles.devforum_regular", "null").unwrap();
+        assert!(!edited.contains("devforum_regular"));
+    }
+
+    #[test]
+    fn parses_values() {
+        let cfg: Config = serde_yaml::from_str(SOURCE).unwrap();
+        let bypass = setting("roles.cooldown_bypass").unwrap();
+
+        let edited = apply(&cfg, bypass, "<@&7>, 8").unwrap();
+        assert_eq!(bypass.get(&edited), r#"["7", "8"]"#);
+        assert_eq!(
+            apply(&cfg, bypass, "moderators").unwrap_err(),
+            EditError::InvalidRole("moderators".to_string())
+        );
+
+        let regular = setting("roles.devforum_regular").unwrap();
+        assert_eq!(
+            apply(&cfg, regular, "1")
+                .unwrap_err()
+                .message(&Locale::French),
+            "Configuration invalide :\n\
+             - roles.devforum_regular : le rôle `1` est aussi utilisé par roles.devforum_member"
+        );
+    }
+}
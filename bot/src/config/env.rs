@@ -0,0 +1,135 @@
+use anyhow::Context;
+
+use super::{Config, LoggingConfig};
+
+/// Applies an override to the config, from the value of its env.
+type Override<T> = fn(&mut T, String) -> anyhow::Result<()>;
+
+/// The settings of the config overridden by the env of the same name.
+const CONFIG_OVERRIDES: &[(&str, Override<Config>)] = &[
+    ("DISCORD_TOKEN", |cfg, value| {
+        cfg.secrets.discord_token = Some(value.into());
+        Ok(())
+    }),
+    ("ROVER_API_KEY", |cfg, value| {
+        cfg.secrets.rover_api_key = Some(value.into());
+        Ok(())
+    }),
+    ("DEVFORUM_COOKIE", |cfg, value| {
+        cfg.secrets.devforum_cookie = Some(value.into());
+        Ok(())
+    }),
+    ("HEALTH_ADDRESS", |cfg, value| {
+        cfg.health_address = Some(value.parse().context("parse socket address")?);
+        Ok(())
+    }),
+];
+
+/// The settings of the logging config overridden by the env of the same name.
+const LOGGING_OVERRIDES: &[(&str, Override<LoggingConfig>)] = &[
+    ("LOG_FORMAT", |cfg, value| {
+        cfg.format = value.parse()?;
+        Ok(())
+    }),
+    ("RUST_LOG", |cfg, value| {
+        cfg.filter = Some(value);
+        Ok(())
+    }),
+];
+
+/// Overrides the settings of the config with the process environment.
+pub(crate) fn apply(cfg: &mut Config) -> anyhow::Result<()> {
+    apply_with(cfg, CONFIG_OVERRIDES, |name| std::env::var(name).ok())?;
+    apply_logging(&mut cfg.logging)
+}
+
+/// Overrides the settings of the logging config with the process environment.
+pub(crate) fn apply_logging(cfg: &mut LoggingConfig) -> anyhow::Result<()> {
+    apply_with(cfg, LOGGING_OVERRIDES, |name| std::env::var(name).ok())
+}
+
+/// Overrides the settings with the values of their env, then with the
+/// contents of the file at their `*_FILE` env, such as Docker secrets.
+fn apply_with<T>(
+    cfg: &mut T,
+    overrides: &[(&str, Override<T>)],
+    env: impl Fn(&str) -> Option<String>,
+) -> anyhow::Result<()> {
+    for (name, apply) in overrides {
+        if let Some(value) = env(name) {
+            apply(cfg, value).with_context(|| format!("override with {name} env"))?;
+        }
+
+        let file_env = format!("{name}_FILE");
+        if let Some(path) = env(&file_env) {
+            let value = std::fs::read_to_string(&path)
+                .with_context(|| format!("read {file_env} file: {path}"))?;
+            // Secret files usually end with a newline.
+            let value = value.trim_end_matches(['\r', '\n']).to_string();
+            apply(cfg, value).with_context(|| format!("override with {file_env} file"))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::config::LogFormat;
+    use crate::testing::TempDir;
+
+    fn config() -> Config {
+        serde_yaml::from_str(
+            r#"
+            roles:
+              devforum_member: "1"
+              devforum_regular: "2"
+            faq_options: []
+            secrets:
+              discord_token: "yaml"
+              rover_api_key: "yaml"
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn env<'a>(vars: &'a HashMap<&str, String>) -> impl Fn(&str) -> Option<String> + 'a {
+        |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn precedence() {
+        let dir = TempDir::new("config-env-precedence");
+        let path = dir.write("rover_api_key", "file\n");
+
+        let vars = HashMap::from([
+            ("DISCORD_TOKEN", "env".to_string()),
+            ("ROVER_API_KEY", "env".to_string()),
+            ("ROVER_API_KEY_FILE", path.display().to_string()),
+        ]);
+        let mut cfg = config();
+        apply_with(&mut cfg, CONFIG_OVERRIDES, env(&vars)).unwrap();
+
+        let secrets = &cfg.secrets;
+        assert_eq!(secrets.discord_token.as_ref().unwrap().expose(), "env");
+        assert_eq!(secrets.rover_api_key.as_ref().unwrap().expose(), "file");
+        assert!(secrets.devforum_cookie.is_none());
+    }
+
+    #[test]
+    fn invalid_values() {
+        let vars = HashMap::from([("HEALTH_ADDRESS", "localhost".to_string())]);
+        let error = apply_with(&mut config(), CONFIG_OVERRIDES, env(&vars)).unwrap_err();
+        assert_eq!(error.to_string(), "override with HEALTH_ADDRESS env");
+
+        let vars = HashMap::from([("DEVFORUM_COOKIE_FILE", "/nonexistent".to_string())]);
+        assert!(apply_with(&mut config(), CONFIG_OVERRIDES, env(&vars)).is_err());
+
+        let vars = HashMap::from([("LOG_FORMAT", "json".to_string())]);
+        let mut logging = LoggingConfig::default();
+        apply_with(&mut logging, LOGGING_OVERRIDES, env(&vars)).unwrap();
+        assert_eq!(logging.format, LogFormat::Json);
+    }
+}
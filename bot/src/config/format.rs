@@ -0,0 +1,137 @@
+use std::fmt::{self, Display};
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::Context;
+use serde_yaml::Value;
+
+use super::Config;
+
+/// The formats the config file can be written in, chosen by its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ConfigFormat {
+    Yaml,
+    Toml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Returns the format of the file at the given path.
+    pub(crate) fn from_path(path: &Path) -> anyhow::Result<Self> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("yml" | "yaml") => Ok(ConfigFormat::Yaml),
+            Some("toml") => Ok(ConfigFormat::Toml),
+            Some("json") => Ok(ConfigFormat::Json),
+            _ => anyhow::bail!(
+                "unknown config format of {}, expected a .yml, .yaml, .toml or .json file",
+                path.display()
+            ),
+        }
+    }
+
+    /// Parses a document in this format.
+    ///
+    /// Every format is parsed into a YAML value, since YAML can represent the others.
+    pub(crate) fn parse(self, source: &str) -> anyhow::Result<Value> {
+        match self {
+            ConfigFormat::Yaml => serde_yaml::from_str(source).map_err(anyhow::Error::from),
+            ConfigFormat::Toml => toml::from_str(source).map_err(anyhow::Error::from),
+            ConfigFormat::Json => serde_json::from_str(source).map_err(anyhow::Error::from),
+        }
+    }
+}
+
+/// The formats the config can be exported in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExportFormat {
+    /// The debug representation of the config.
+    Rust,
+    Yaml,
+    Toml,
+    Json,
+}
+
+impl ExportFormat {
+    /// Every export format, in the order they're offered.
+    pub(crate) const ALL: [ExportFormat; 4] = [
+        ExportFormat::Rust,
+        ExportFormat::Yaml,
+        ExportFormat::Toml,
+        ExportFormat::Json,
+    ];
+
+    /// Returns the name of the exported file.
+    pub(crate) fn file_name(self) -> &'static str {
+        match self {
+            ExportFormat::Rust => "magnolia.cfg.rs",
+            ExportFormat::Yaml => "magnolia.cfg.yml",
+            ExportFormat::Toml => "magnolia.cfg.toml",
+            ExportFormat::Json => "magnolia.cfg.json",
+        }
+    }
+}
+
+impl Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportFormat::Rust => write!(f, "Rust"),
+            ExportFormat::Yaml => write!(f, "YAML"),
+            ExportFormat::Toml => write!(f, "TOML"),
+            ExportFormat::Json => write!(f, "JSON"),
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Rust" => Ok(ExportFormat::Rust),
+            "YAML" => Ok(ExportFormat::Yaml),
+            "TOML" => Ok(ExportFormat::Toml),
+            "JSON" => Ok(ExportFormat::Json),
+            _ => anyhow::bail!("unknown export format: {s}"),
+        }
+    }
+}
+
+impl Config {
+    /// Exports the config in the given format, with the [secrets](super::Secret) redacted.
+    pub(crate) fn export(&self, format: ExportFormat) -> anyhow::Result<String> {
+        match format {
+            ExportFormat::Rust => Ok(format!("{self:#?}")),
+            ExportFormat::Yaml => serde_yaml::to_string(self).context("serialize config to YAML"),
+            ExportFormat::Toml => toml::to_string_pretty(self).context("serialize config to TOML"),
+            ExportFormat::Json => {
+                serde_json::to_string_pretty(self).context("serialize config to JSON")
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::load_config_with;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn exports_load_back() {
+        let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.yml");
+        let cfg = load_config_with(fixture, |_| Ok(())).unwrap();
+        let dir = TempDir::new("config-format");
+
+        for format in [ExportFormat::Yaml, ExportFormat::Toml, ExportFormat::Json] {
+            let path = dir.write(format.file_name(), &cfg.export(format).unwrap());
+
+            let loaded = load_config_with(&path, |_| Ok(())).unwrap();
+            assert_eq!(loaded.roles.roblox_verified, cfg.roles.roblox_verified);
+            assert_eq!(
+                loaded.faq_option_response("bug-report"),
+                cfg.faq_option_response("bug-report"),
+                "{format} export doesn't load back"
+            );
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde_yaml::Value;
+
+use super::format::ConfigFormat;
+
+/// The key of the directive listing the files merged into a config file,
+/// relative to the file.
+pub(crate) const INCLUDE_KEY: &str = "include";
+
+/// Merges the files included by the document of the config file at the given path.
+///
+/// Included files can be in any [format](ConfigFormat) and include other files.
+/// Their mappings are merged into the including document and their sequences are
+/// appended, such as the FAQ options, while its other values take precedence.
+pub(crate) fn resolve(document: Value, path: &Path) -> anyhow::Result<Value> {
+    resolve_with(document, path, &mut Vec::new())
+}
+
+/// Resolves the includes of the document, with the files being resolved.
+fn resolve_with(
+    mut document: Value,
+    path: &Path,
+    resolving: &mut Vec<PathBuf>,
+) -> anyhow::Result<Value> {
+    let includes = match document
+        .as_mapping_mut()
+        .and_then(|mapping| mapping.remove(INCLUDE_KEY))
+    {
+        Some(includes) => serde_yaml::from_value::<Vec<PathBuf>>(includes)
+            .with_context(|| format!("parse {INCLUDE_KEY} of {}", path.display()))?,
+        None => return Ok(document),
+    };
+
+    let canonical = path
+        .canonicalize()
+        .with_context(|| format!("resolve path: {}", path.display()))?;
+    if resolving.contains(&canonical) {
+        anyhow::bail!("{} is included by itself", path.display());
+    }
+
+    resolving.push(canonical);
+    let directory = path.parent().unwrap_or(Path::new(""));
+    for include in includes {
+        let include = directory.join(include);
+        let source = std::fs::read_to_string(&include)
+            .with_context(|| format!("read included file: {}", include.display()))?;
+        let included = ConfigFormat::from_path(&include)?
+            .parse(&source)
+            .with_context(|| format!("parse included file: {}", include.display()))?;
+
+        merge(&mut document, resolve_with(included, &include, resolving)?);
+    }
+    resolving.pop();
+
+    Ok(document)
+}
+
+/// Merges the included value into the value of the including document.
+fn merge(value: &mut Value, included: Value) {
+    match (value, included) {
+        (Value::Mapping(mapping), Value::Mapping(included)) => {
+            for (key, included) in included {
+                match mapping.get_mut(&key) {
+                    Some(value) => merge(value, included),
+                    None => {
+                        mapping.insert(key, included);
+                    },
+                }
+            }
+        },
+        (Value::Sequence(sequence), Value::Sequence(included)) => sequence.extend(included),
+        (value @ Value::Null, included) => *value = included,
+        _ => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn merges_included_files() {
+        let dir = TempDir::new("config-include");
+        dir.write(
+            "faq/bugs.toml",
+            "[[faq_options]]\nlabel = \"Bugs\"\nvalue = \"bugs\"\nresponse = { content = \"Report them.\" }\n",
+        );
+        dir.write(
+            "faq/roles.json",
+            r#"{ "include": ["bugs.toml"], "roles": { "devforum_member": "3" } }"#,
+        );
+        let looped = dir.write("loop.yml", "include: [loop.yml]\n");
+        let path = dir.write("magnolia.cfg.yml", "");
+
+        let document: Value = serde_yaml::from_str(
+            r#"
+            include: ["faq/roles.json"]
+            roles:
+              devforum_member: "1"
+              devforum_regular: "2"
+            faq_options:
+              - label: "Roles"
+                value: "roles"
+                response: { content: "Verify." }
+            "#,
+        )
+        .unwrap();
+        let document = resolve(document, &path).unwrap();
+
+        assert_eq!(document.get(INCLUDE_KEY), None);
+        assert_eq!(document["roles"]["devforum_member"], "1");
+        assert_eq!(document["faq_options"][0]["value"], "roles");
+        assert_eq!(document["faq_options"][1]["value"], "bugs");
+
+        let document = serde_yaml::from_str("include: [loop.yml]").unwrap();
+        let error = resolve(document, &looped).unwrap_err();
+        assert!(error
+            .to_string()
+            .ends_with("loop.yml is included by itself"));
+    }
+}
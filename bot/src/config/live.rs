@@ -0,0 +1,158 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+use anyhow::Context;
+use builders::locale::Locale;
+use tokio::sync::Mutex;
+
+use super::edit::{self, Setting, SETTINGS};
+use super::format::ConfigFormat;
+use super::Config;
+use crate::error::InteractionError;
+
+/// The config used by the handlers, which can be edited while the bot runs.
+#[derive(Debug)]
+pub(crate) struct LiveConfig {
+    /// The path of the config file, which edits are written back to.
+    path: PathBuf,
+    /// The config loaded at startup.
+    initial: Arc<Config>,
+    current: RwLock<Arc<Config>>,
+    /// Held while editing, so concurrent edits don't overwrite each other.
+    editing: Mutex<()>,
+    /// Whether the config file matches the current config,
+    /// which stops being the case if an edit fails to be written back.
+    loaded: AtomicBool,
+}
+
+/// A setting changed since startup.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Change {
+    pub(crate) path: &'static str,
+    pub(crate) initial: String,
+    pub(crate) current: String,
+}
+
+impl LiveConfig {
+    /// Creates the live config from the config loaded from the given path.
+    pub(crate) fn new(cfg: Config, path: PathBuf) -> Self {
+        let cfg = Arc::new(cfg);
+        Self {
+            path,
+            initial: cfg.clone(),
+            current: RwLock::new(cfg),
+            editing: Mutex::default(),
+            loaded: AtomicBool::new(true),
+        }
+    }
+
+    /// Returns whether the config file was loaded and every edit was written back to it.
+    pub(crate) fn loaded(&self) -> bool {
+        self.loaded.load(Ordering::Relaxed)
+    }
+
+    /// Returns the current config.
+    pub(crate) fn get(&self) -> Arc<Config> {
+        self.current.read().expect("config lock poisoned").clone()
+    }
+
+    /// Changes the setting to the given value and applies it.
+    ///
+    /// The config file is written back, keeping the previous one as a backup.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [user error](InteractionError::User) in the locale if the value is invalid,
+    /// or an error if the config file can't be written.
+    pub(crate) async fn set(
+        &self,
+        setting: &Setting,
+        value: &str,
+        locale: &Locale,
+    ) -> anyhow::Result<Arc<Config>> {
+        let _editing = self.editing.lock().await;
+
+        let cfg = edit::apply(&self.get(), setting, value)
+            .map_err(|error| InteractionError::user(error.message(locale)))?;
+        let (file, path, value) = (self.path.clone(), setting.path, setting.get(&cfg));
+        let written = tokio::task::spawn_blocking(move || write_back(&file, path, &value))
+            .await
+            .context("join config write back")
+            .and_then(|written| written);
+        self.loaded.store(written.is_ok(), Ordering::Relaxed);
+        written?;
+
+        let cfg = Arc::new(cfg);
+        *self.current.write().expect("config lock poisoned") = cfg.clone();
+        tracing::info!(
+            path = setting.path,
+            value = setting.get(&cfg),
+            "edited config"
+        );
+        Ok(cfg)
+    }
+
+    /// Returns the settings changed since startup.
+    pub(crate) fn diff(&self) -> Vec<Change> {
+        let current = self.get();
+        SETTINGS
+            .iter()
+            .map(|setting| Change {
+                path: setting.path,
+                initial: setting.get(&self.initial),
+                current: setting.get(&current),
+            })
+            .filter(|change| change.initial != change.current)
+            .collect()
+    }
+}
+
+/// Writes the value at the path to the config file,
+/// after copying the file to the same path suffixed by `.bak`.
+fn write_back(file: &Path, path: &str, value: &str) -> anyhow::Result<()> {
+    let source = std::fs::read_to_string(file).context("read config file")?;
+    let format = ConfigFormat::from_path(file)?;
+    let edited = edit::edit_source(format, &source, path, value)?;
+
+    std::fs::copy(file, suffixed(file, ".bak")).context("back up config file")?;
+    // Write to another file first, so the config file is never partially written.
+    let temporary = suffixed(file, ".tmp");
+    std::fs::write(&temporary, edited).context("write config file")?;
+    std::fs::rename(&temporary, file).context("replace config file")
+}
+
+/// Returns the path with the suffix appended to its file name.
+fn suffixed(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = OsString::from(path);
+    path.push(suffix);
+    path.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn failed_write_back_unloads() {
+        let path = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/config.yml"
+        ));
+        let cfg = super::super::load_config_with(&path, |_| Ok(())).unwrap();
+        let live = LiveConfig::new(cfg, PathBuf::from("missing/magnolia.cfg.yml"));
+        assert!(live.loaded());
+
+        let setting = edit::setting("roles.roblox_verified").unwrap();
+        let result = live.set(setting, "1003", &crate::i18n::FALLBACK).await;
+
+        assert!(result.is_err());
+        assert!(!live.loaded());
+        // The current config is left untouched
+        assert_eq!(
+            live.get().roles.roblox_verified,
+            live.initial.roles.roblox_verified
+        );
+    }
+}
@@ -0,0 +1,422 @@
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::Context;
+use builders::component::SelectMenuOptionBuilder;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize, Serializer};
+use twilight_model::application::command::{CommandOptionChoice, CommandOptionChoiceValue};
+use twilight_model::channel::message::component::SelectMenuOption;
+use twilight_model::http::interaction::InteractionResponseData;
+use twilight_model::id::marker::RoleMarker;
+use twilight_model::id::Id;
+
+pub(crate) mod edit;
+pub(crate) mod env;
+pub(crate) mod format;
+pub(crate) mod include;
+pub(crate) mod live;
+pub(crate) mod schema;
+pub(crate) mod validate;
+
+use format::ConfigFormat;
+use validate::ConfigError;
+
+/// Replaces the value of secrets in exports.
+const REDACTED: &str = "[REDACTED]";
+
+/// Configuration for the bot.
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone)]
+pub(crate) struct Config {
+    /// A mapping of role IDs to their names.
+    pub(crate) roles: RoleConfig,
+    /// A list of options for the FAQ command.
+    faq_options: Vec<FaqOption>,
+    /// The range of shards run by this process,
+    /// defaults to all the shards recommended by Discord.
+    #[serde(default)]
+    pub(crate) shards: Option<ShardRange>,
+    /// The address to serve the health and readiness endpoints on,
+    /// the endpoints are disabled if not set.
+    #[serde(default)]
+    pub(crate) health_address: Option<SocketAddr>,
+    /// The format, filters and destinations of the logs.
+    #[serde(default)]
+    pub(crate) logging: LoggingConfig,
+    /// The credentials of the APIs, overridden by the env of
+    /// the same name in uppercase or a file at its `*_FILE` env.
+    #[serde(default)]
+    pub(crate) secrets: SecretsConfig,
+}
+
+/// Configuration for the credentials of the APIs.
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Default, Clone)]
+pub(crate) struct SecretsConfig {
+    /// The token of the bot.
+    #[schemars(with = "Option<String>")]
+    pub(crate) discord_token: Option<Secret>,
+    /// The key of the RoVer API, used to verify the DevForum rank of members.
+    #[schemars(with = "Option<String>")]
+    pub(crate) rover_api_key: Option<Secret>,
+    /// The `_t` cookie of a DevForum account, used when a profile is hidden to visitors.
+    #[schemars(with = "Option<String>")]
+    pub(crate) devforum_cookie: Option<Secret>,
+}
+
+impl SecretsConfig {
+    /// Returns the token of the bot.
+    pub(crate) fn discord_token(&self) -> anyhow::Result<String> {
+        required(&self.discord_token, "discord_token")
+    }
+
+    /// Returns the key of the RoVer API.
+    pub(crate) fn rover_api_key(&self) -> anyhow::Result<String> {
+        required(&self.rover_api_key, "rover_api_key")
+    }
+}
+
+/// Returns the value of a required secret, explaining how to set it if it's missing.
+fn required(secret: &Option<Secret>, name: &str) -> anyhow::Result<String> {
+    let env = name.to_uppercase();
+    secret
+        .as_ref()
+        .map(|secret| secret.expose().to_string())
+        .with_context(|| {
+            format!("missing secrets.{name}, set it in the config file, the {env} env or a file at the {env}_FILE env")
+        })
+}
+
+/// A credential, which is redacted when debug formatted or serialized.
+#[derive(Deserialize, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub(crate) struct Secret(String);
+
+impl Secret {
+    /// Returns the value of the secret.
+    pub(crate) fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Secret({REDACTED})")
+    }
+}
+
+/// Configuration for logging.
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Default, Clone)]
+pub(crate) struct LoggingConfig {
+    /// The format of the log lines, overridden by the `LOG_FORMAT` env.
+    #[serde(default)]
+    pub(crate) format: LogFormat,
+    /// The filter directives (e.g. `info,bot::commands=debug`),
+    /// overridden by the `RUST_LOG` env.
+    pub(crate) filter: Option<String>,
+    /// Also write the logs to rotating files.
+    pub(crate) file: Option<LogFileConfig>,
+}
+
+/// The format of the log lines.
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum LogFormat {
+    /// Human-readable text.
+    #[default]
+    Text,
+    /// One JSON object per line.
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => anyhow::bail!("unknown log format: {s}"),
+        }
+    }
+}
+
+/// Configuration for writing the logs to rotating files.
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone)]
+pub(crate) struct LogFileConfig {
+    /// The directory the log files are written to.
+    pub(crate) directory: PathBuf,
+    /// The prefix of the log file names, followed by the date.
+    pub(crate) prefix: String,
+    /// How often a new log file is started.
+    #[serde(default)]
+    pub(crate) rotation: LogRotation,
+}
+
+/// How often a new log file is started.
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum LogRotation {
+    Minutely,
+    Hourly,
+    #[default]
+    Daily,
+    Never,
+}
+
+/// Configuration for the range of shards run by this process.
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone)]
+pub(crate) struct ShardRange {
+    /// The ID of the first shard (inclusive).
+    pub(crate) start: u32,
+    /// The ID of the last shard (exclusive).
+    pub(crate) end: u32,
+    /// The total number of shards across all processes.
+    pub(crate) total: u32,
+}
+
+impl ShardRange {
+    /// Ensures the range is non-empty and within the total number of shards.
+    pub(crate) fn validate(&self) -> anyhow::Result<()> {
+        if self.start >= self.end || self.end > self.total {
+            anyhow::bail!(
+                "invalid shard range {}..{} for a total of {} shards",
+                self.start,
+                self.end,
+                self.total
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Configuration for roles.
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone)]
+pub(crate) struct RoleConfig {
+    /// The role of members with the DevForum member trust level.
+    #[schemars(with = "schema::Snowflake")]
+    pub(crate) devforum_member: Id<RoleMarker>,
+    /// The role of members with the DevForum regular trust level.
+    #[schemars(with = "schema::Snowflake")]
+    pub(crate) devforum_regular: Id<RoleMarker>,
+    /// The role of members verified with RoVer,
+    /// allows the bot to avoid making unnecessary API calls.
+    #[schemars(with = "Option<schema::Snowflake>")]
+    pub(crate) roblox_verified: Option<Id<RoleMarker>>,
+    /// Roles whose members bypass the cooldowns of commands and components.
+    #[serde(default)]
+    #[schemars(with = "Vec<schema::Snowflake>")]
+    pub(crate) cooldown_bypass: Vec<Id<RoleMarker>>,
+}
+
+/// Configuration for an option of the FAQ command.
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone)]
+pub(crate) struct FaqOption {
+    /// The label of the option (displayed to the user).
+    label: String,
+    /// The value of the option (used as the identifier).
+    value: String,
+    /// The embed to be sent when this option is selected.
+    // pub(crate) embed: Embed,
+    /// The components to be included with the embed.
+    // pub(crate) components: Option<Vec<Component>>,
+    #[schemars(with = "schema::ResponseData")]
+    pub(crate) response: InteractionResponseData,
+}
+
+impl Config {
+    /// Returns a vector of options for the FAQ command.
+    pub(crate) fn faq_option_choices(&self) -> Vec<CommandOptionChoice> {
+        self.faq_options
+            .iter()
+            .map(|opt| CommandOptionChoice {
+                name: opt.label.clone(),
+                value: CommandOptionChoiceValue::String(opt.value.clone()),
+                name_localizations: None,
+            })
+            .collect()
+    }
+
+    /// Returns a vector of select menu options for the FAQ browser,
+    /// marking the option with the given value as selected.
+    pub(crate) fn faq_select_options(&self, selected: Option<&str>) -> Vec<SelectMenuOption> {
+        self.faq_options
+            .iter()
+            .map(|opt| {
+                SelectMenuOptionBuilder::new(&opt.label, &opt.value)
+                    .default(selected == Some(opt.value.as_str()))
+                    .build()
+            })
+            .collect()
+    }
+
+    /// Returns the number of FAQ options.
+    pub(crate) fn faq_option_count(&self) -> usize {
+        self.faq_options.len()
+    }
+
+    /// Returns the position of the FAQ option with the given value.
+    pub(crate) fn faq_option_index(&self, value: &str) -> Option<usize> {
+        self.faq_options.iter().position(|opt| opt.value == value)
+    }
+
+    /// Returns the value of the FAQ option at the given position.
+    pub(crate) fn faq_option_value(&self, index: usize) -> Option<&str> {
+        self.faq_options.get(index).map(|opt| opt.value.as_str())
+    }
+
+    /// Returns the FAQ option corresponding to the given value.
+    pub(crate) fn faq_option_response<S>(&self, value: S) -> Option<InteractionResponseData>
+    where
+        S: AsRef<str>,
+    {
+        self.faq_options
+            .iter()
+            .find(|opt| opt.value == value.as_ref())
+            .map(|opt| opt.response.clone())
+    }
+}
+
+/// Loads the configuration from a YAML, TOML or JSON file, merging the files it
+/// [includes](include::resolve), overridden by the [environment](env::apply).
+///
+/// # Errors
+///
+/// Returns an error if a file can't be parsed, an override is invalid, or listing
+/// every invalid field and [semantic error](validate::validate), with its line
+/// if the file is in YAML.
+pub(crate) fn load_config(path: impl AsRef<Path>) -> Result<Config, anyhow::Error> {
+    load_config_with(path, env::apply)
+}
+
+/// Loads the configuration like [`load_config`], with the given overrides instead
+/// of the environment, such as none for the tests.
+pub(crate) fn load_config_with(
+    path: impl AsRef<Path>,
+    overrides: impl FnOnce(&mut Config) -> anyhow::Result<()>,
+) -> Result<Config, anyhow::Error> {
+    let path = path.as_ref();
+    let format = ConfigFormat::from_path(path)?;
+    let source = std::fs::read_to_string(path).context("read config file")?;
+    let document = format.parse(&source).context("parse config file")?;
+    let includes = document.get(include::INCLUDE_KEY).is_some();
+    let document = include::resolve(document, path)?;
+
+    let mut errors = Vec::new();
+    let cfg = match serde_path_to_error::deserialize::<_, Config>(document) {
+        Ok(mut cfg) => {
+            overrides(&mut cfg)?;
+            errors = validate::validate(&cfg);
+            Some(cfg)
+        },
+        // Errors of the whole document don't have a path to locate.
+        Err(error) if error.path().to_string() == "." => {
+            return Err(error.into_inner()).context("parse config file");
+        },
+        Err(error) => {
+            errors.push(ConfigError::invalid(
+                error.path().to_string(),
+                error.inner(),
+            ));
+            None
+        },
+    };
+
+    match cfg {
+        Some(cfg) if errors.is_empty() => Ok(cfg),
+        _ => {
+            if format == ConfigFormat::Yaml {
+                validate::locate(&mut errors, &source, includes);
+            }
+            let errors: Vec<String> = errors.iter().map(|error| format!("  {error}")).collect();
+            anyhow::bail!("invalid config file:\n{}", errors.join("\n"))
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format::ExportFormat;
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn exports_redact_secrets() {
+        let mut cfg: Config = serde_yaml::from_str(
+            r#"
+            roles:
+              devforum_member: "1"
+              devforum_regular: "2"
+            faq_options: []
+            "#,
+        )
+        .unwrap();
+        cfg.secrets.discord_token = Some("hunter2".to_string().into());
+
+        for format in ExportFormat::ALL {
+            let export = cfg.export(format).unwrap();
+            assert!(
+                !export.contains("hunter2"),
+                "{format} export leaks a secret"
+            );
+            assert!(
+                export.contains(REDACTED),
+                "{format} export lacks the secret"
+            );
+        }
+
+        let export: Config =
+            serde_yaml::from_str(&cfg.export(ExportFormat::Yaml).unwrap()).unwrap();
+        assert_eq!(export.roles.devforum_member, Id::new(1));
+    }
+
+    #[test]
+    fn locates_invalid_fields() {
+        let dir = TempDir::new("config-located");
+        let path = dir.write("magnolia.cfg.yml", "roles:\n  devforum_member: true\n");
+
+        let error = load_config_with(&path, |_| Ok(())).unwrap_err().to_string();
+        assert!(
+            error.starts_with(
+                "invalid config file:\n  roles.devforum_member (line 2): invalid type"
+            ),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn leaves_included_fields_unlocated() {
+        let dir = TempDir::new("config-included");
+        dir.write(
+            "faq.toml",
+            "[[faq_options]]\nlabel = \"Bugs\"\nvalue = \"bugs\"\nresponse = {}\n",
+        );
+        let path = dir.write(
+            "magnolia.cfg.yml",
+            "include: [faq.toml]\nroles:\n  devforum_member: \"1\"\n  devforum_regular: \"1\"\nfaq_options: []\n",
+        );
+
+        let error = load_config_with(&path, |_| Ok(())).unwrap_err().to_string();
+        assert!(
+            error.contains("roles.devforum_regular (line 4): role `1`"),
+            "{error}"
+        );
+        assert!(
+            error.contains("faq_options[0].response: must have"),
+            "{error}"
+        );
+    }
+}
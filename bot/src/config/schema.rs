@@ -0,0 +1,167 @@
+use std::borrow::Cow;
+
+use schemars::generate::SchemaSettings;
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use twilight_validate::embed::{
+    AUTHOR_NAME_LENGTH, COLOR_MAXIMUM, DESCRIPTION_LENGTH, FIELD_COUNT, FIELD_NAME_LENGTH,
+    FIELD_VALUE_LENGTH, FOOTER_TEXT_LENGTH, TITLE_LENGTH,
+};
+use twilight_validate::message::{EMBED_COUNT_LIMIT, MESSAGE_CONTENT_LENGTH_MAX};
+
+use super::include::INCLUDE_KEY;
+use super::Config;
+
+/// Returns the JSON Schema of the config file, for editors and CI to validate it against.
+pub(crate) fn config_schema() -> Schema {
+    let mut schema =
+        SchemaGenerator::new(SchemaSettings::draft07()).into_root_schema_for::<Config>();
+
+    // Any field can be in an included file instead.
+    schema.remove("required");
+
+    // The include directive is resolved before deserializing the config.
+    if let Some(properties) = schema
+        .get_mut("properties")
+        .and_then(|properties| properties.as_object_mut())
+    {
+        properties.insert(
+            INCLUDE_KEY.to_string(),
+            json_schema!({
+                "description": "Other config files merged into this one, relative to it. \
+                    Their mappings are merged and their lists, such as the FAQ options, are appended.",
+                "type": "array",
+                "items": { "type": "string", "pattern": "\\.(ya?ml|toml|json)$" },
+            })
+            .into(),
+        );
+    }
+
+    schema
+}
+
+/// The schema of a Discord ID, which is either a string or an integer.
+pub(super) struct Snowflake;
+
+impl JsonSchema for Snowflake {
+    fn schema_name() -> Cow<'static, str> {
+        "Snowflake".into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "A Discord ID.",
+            "type": ["string", "integer"],
+            "pattern": "^[0-9]{1,20}$",
+            "minimum": 1,
+        })
+    }
+}
+
+/// The schema of the message sent in response to an interaction.
+///
+/// Only describes the commonly used fields and limits, the complete
+/// message is checked by [`validate`](super::validate::validate).
+pub(super) struct ResponseData;
+
+impl JsonSchema for ResponseData {
+    fn schema_name() -> Cow<'static, str> {
+        "ResponseData".into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        let text = |max_length: usize| json_schema!({ "type": "string", "maxLength": max_length });
+        let url = json_schema!({
+            "type": "object",
+            "required": ["url"],
+            "properties": { "url": { "type": "string" } },
+        });
+
+        json_schema!({
+            "description": "The message sent in response, with at least content, embeds or components.",
+            "type": "object",
+            "properties": {
+                "content": text(MESSAGE_CONTENT_LENGTH_MAX),
+                "embeds": {
+                    "type": "array",
+                    "maxItems": EMBED_COUNT_LIMIT,
+                    "items": {
+                        "type": "object",
+                        "required": ["type"],
+                        "properties": {
+                            "type": { "type": "string", "default": "rich" },
+                            "title": text(TITLE_LENGTH),
+                            "description": text(DESCRIPTION_LENGTH),
+                            "url": { "type": "string" },
+                            "color": { "type": "integer", "minimum": 0, "maximum": COLOR_MAXIMUM },
+                            "author": {
+                                "type": "object",
+                                "required": ["name"],
+                                "properties": { "name": text(AUTHOR_NAME_LENGTH) },
+                            },
+                            "footer": {
+                                "type": "object",
+                                "required": ["text"],
+                                "properties": { "text": text(FOOTER_TEXT_LENGTH) },
+                            },
+                            "fields": {
+                                "type": "array",
+                                "maxItems": FIELD_COUNT,
+                                "items": {
+                                    "type": "object",
+                                    "required": ["name", "value"],
+                                    "properties": {
+                                        "name": text(FIELD_NAME_LENGTH),
+                                        "value": text(FIELD_VALUE_LENGTH),
+                                        "inline": { "type": "boolean" },
+                                    },
+                                },
+                            },
+                            "image": url,
+                            "thumbnail": url,
+                        },
+                    },
+                },
+                "components": {
+                    "description": "The action rows of the message, the FAQ browser adds its own below them.",
+                    "type": "array",
+                    "maxItems": 4,
+                    "items": {
+                        "type": "object",
+                        "required": ["type", "components"],
+                        "properties": {
+                            "type": { "const": 1 },
+                            "components": {
+                                "type": "array",
+                                "minItems": 1,
+                                "maxItems": 5,
+                                "items": {
+                                    "type": "object",
+                                    "required": ["type"],
+                                    "properties": { "type": { "type": "integer" } },
+                                },
+                            },
+                        },
+                    },
+                },
+                "flags": { "type": "integer" },
+                "tts": { "type": "boolean" },
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn committed_schema_is_up_to_date() {
+        let schema = serde_json::to_string_pretty(&config_schema()).unwrap() + "\n";
+
+        assert_eq!(
+            schema,
+            include_str!("../../../magnolia.cfg.schema.json"),
+            "run `bot config-schema > magnolia.cfg.schema.json` to update the schema"
+        );
+    }
+}
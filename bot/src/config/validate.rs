@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+use builders::component::ActionRowBuilder;
+use builders::locale::Locale;
+use twilight_model::channel::message::Component;
+use twilight_model::http::interaction::InteractionResponseData;
+use twilight_validate::command::{
+    CHOICES_LIMIT, OPTION_CHOICE_NAME_LENGTH_MAX, OPTION_CHOICE_STRING_VALUE_LENGTH_MAX,
+};
+use twilight_validate::component::{
+    SELECT_OPTION_COUNT, SELECT_OPTION_LABEL_LENGTH, SELECT_OPTION_VALUE_LENGTH,
+};
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust2::scanner::Marker;
+
+use super::Config;
+use crate::i18n;
+
+// The FAQ options are listed both as command option choices and select menu options.
+const FAQ_OPTION_COUNT: usize = min(CHOICES_LIMIT, SELECT_OPTION_COUNT);
+const FAQ_LABEL_LENGTH: usize = min(OPTION_CHOICE_NAME_LENGTH_MAX, SELECT_OPTION_LABEL_LENGTH);
+const FAQ_VALUE_LENGTH: usize = min(
+    OPTION_CHOICE_STRING_VALUE_LENGTH_MAX,
+    SELECT_OPTION_VALUE_LENGTH,
+);
+/// The maximum number of action rows in a message.
+pub(crate) const ACTION_ROW_COUNT: usize = 5;
+
+/// A semantic error in the config, located by its path (e.g. `faq_options[1].value`).
+///
+/// Displayed in the [fallback](i18n::FALLBACK) locale.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ConfigError {
+    pub(crate) path: String,
+    /// The key of the message in the `config` section of the catalogues.
+    key: &'static str,
+    args: Vec<(&'static str, String)>,
+    /// The line of the path in the config file, if known.
+    pub(crate) line: Option<usize>,
+}
+
+impl ConfigError {
+    pub(super) fn new(
+        path: impl Into<String>,
+        key: &'static str,
+        args: &[(&'static str, &dyn Display)],
+    ) -> Self {
+        Self {
+            path: path.into(),
+            key,
+            args: args
+                .iter()
+                .map(|(name, value)| (*name, value.to_string()))
+                .collect(),
+            line: None,
+        }
+    }
+
+    /// Creates an error from an error of a library, which isn't translated.
+    pub(super) fn invalid(path: impl Into<String>, error: impl Display) -> Self {
+        Self::new(path, "invalid", &[("error", &error)])
+    }
+
+    /// Returns the error in the locale, prefixed by its path and line.
+    pub(crate) fn message(&self, locale: &Locale) -> String {
+        let args: Vec<(&str, &dyn Display)> = self
+            .args
+            .iter()
+            .map(|(name, value)| (*name, value as &dyn Display))
+            .collect();
+        let message = i18n::message(locale, &format!("config.{}", self.key), &args);
+
+        match self.line {
+            Some(line) => i18n::message(locale, "config.located", &[
+                ("path", &self.path),
+                ("line", &line),
+                ("message", &message),
+            ]),
+            None => i18n::message(locale, "config.unlocated", &[
+                ("path", &self.path),
+                ("message", &message),
+            ]),
+        }
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message(&i18n::FALLBACK))
+    }
+}
+
+/// Checks the rules that deserialization can't express,
+/// such as Discord's limits and the uniqueness of the FAQ values.
+pub(crate) fn validate(cfg: &Config) -> Vec<ConfigError> {
+    let mut errors = Vec::new();
+    validate_roles(cfg, &mut errors);
+    validate_faq_options(cfg, &mut errors);
+
+    if let Some(shards) = &cfg.shards {
+        if let Err(error) = shards.validate() {
+            errors.push(ConfigError::invalid("shards", error));
+        }
+    }
+
+    errors
+}
+
+fn validate_roles(cfg: &Config, errors: &mut Vec<ConfigError>) {
+    let roles = [
+        ("devforum_member", Some(cfg.roles.devforum_member)),
+        ("devforum_regular", Some(cfg.roles.devforum_regular)),
+        ("roblox_verified", cfg.roles.roblox_verified),
+    ];
+
+    for (i, (name, id)) in roles.iter().enumerate() {
+        let Some(id) = id else {
+            continue;
+        };
+        if let Some((other, _)) = roles[..i].iter().find(|(_, other)| *other == Some(*id)) {
+            errors.push(ConfigError::new(
+                format!("roles.{name}"),
+                "duplicate_role",
+                &[("role", id), ("other", other)],
+            ));
+        }
+    }
+
+    // Members gaining a DevForum role shouldn't also start bypassing the cooldowns.
+    for (i, id) in cfg.roles.cooldown_bypass.iter().enumerate() {
+        if let Some((other, _)) = roles[..2].iter().find(|(_, other)| *other == Some(*id)) {
+            errors.push(ConfigError::new(
+                format!("roles.cooldown_bypass[{i}]"),
+                "duplicate_role",
+                &[("role", id), ("other", other)],
+            ));
+        }
+    }
+}
+
+fn validate_faq_options(cfg: &Config, errors: &mut Vec<ConfigError>) {
+    if cfg.faq_options.len() > FAQ_OPTION_COUNT {
+        errors.push(ConfigError::new("faq_options", "faq_option_count", &[
+            ("max", &FAQ_OPTION_COUNT),
+            ("count", &cfg.faq_options.len()),
+        ]));
+    }
+
+    let mut values: HashMap<&str, usize> = HashMap::new();
+    for (i, option) in cfg.faq_options.iter().enumerate() {
+        let path = format!("faq_options[{i}]");
+
+        let label_length = option.label.chars().count();
+        if label_length == 0 || label_length > FAQ_LABEL_LENGTH {
+            errors.push(ConfigError::new(format!("{path}.label"), "length", &[
+                ("max", &FAQ_LABEL_LENGTH),
+                ("length", &label_length),
+            ]));
+        }
+
+        let value_length = option.value.chars().count();
+        if value_length == 0 || value_length > FAQ_VALUE_LENGTH {
+            errors.push(ConfigError::new(format!("{path}.value"), "length", &[
+                ("max", &FAQ_VALUE_LENGTH),
+                ("length", &value_length),
+            ]));
+        }
+
+        if let Some(first) = values.insert(&option.value, i) {
+            // Keep pointing to the first use of the value.
+            values.insert(&option.value, first);
+            errors.push(ConfigError::new(
+                format!("{path}.value"),
+                "duplicate_faq_value",
+                &[("value", &option.value), ("first", &first)],
+            ));
+        }
+
+        validate_response(&option.response, &format!("{path}.response"), errors);
+    }
+}
+
+const fn min(a: usize, b: usize) -> usize {
+    if a < b {
+        a
+    } else {
+        b
+    }
+}
+
+/// Checks that the response satisfies Discord's message limits.
+fn validate_response(
+    response: &InteractionResponseData,
+    path: &str,
+    errors: &mut Vec<ConfigError>,
+) {
+    let content = response.content.as_deref().unwrap_or_default();
+    let embeds = response.embeds.as_deref().unwrap_or_default();
+    let components = response.components.as_deref().unwrap_or_default();
+
+    if content.is_empty() && embeds.is_empty() && components.is_empty() {
+        errors.push(ConfigError::new(path, "empty_response", &[]));
+    }
+
+    if let Err(error) = twilight_validate::message::content(content) {
+        errors.push(ConfigError::invalid(format!("{path}.content"), error));
+    }
+
+    if let Err(error) = twilight_validate::message::embeds(embeds) {
+        errors.push(ConfigError::invalid(format!("{path}.embeds"), error));
+    }
+
+    // The FAQ browser adds its select menu as another action row.
+    if components.len() >= ACTION_ROW_COUNT {
+        errors.push(ConfigError::new(
+            format!("{path}.components"),
+            "action_row_count",
+            &[
+                ("max", &(ACTION_ROW_COUNT - 1)),
+                ("count", &components.len()),
+            ],
+        ));
+    }
+
+    for (i, component) in components.iter().enumerate() {
+        let path = format!("{path}.components[{i}]");
+        let Component::ActionRow(row) = component else {
+            errors.push(ConfigError::new(path, "not_action_row", &[]));
+            continue;
+        };
+
+        if let Err(error) = ActionRowBuilder::new()
+            .set_components(row.components.clone())
+            .build()
+        {
+            errors.push(ConfigError::invalid(path, format!("{error:#}")));
+        }
+    }
+}
+
+/// Sets the line of each error from the YAML source of the config.
+///
+/// Errors whose path isn't in the source (e.g. a missing optional field)
+/// are located at their closest parent, unless the config includes other files,
+/// which the path may come from.
+pub(crate) fn locate(errors: &mut [ConfigError], source: &str, includes: bool) {
+    let Some(lines) = lines(source) else {
+        return;
+    };
+
+    for error in errors {
+        let mut path = error.path.as_str();
+        error.line = loop {
+            if let Some(line) = lines.get(path) {
+                break Some(*line);
+            }
+            if includes {
+                break None;
+            }
+            match path.rfind(['.', '[']) {
+                Some(end) => path = &path[..end],
+                None => break None,
+            }
+        };
+    }
+}
+
+/// Returns the line of every path in a YAML document,
+/// or [`None`] if the document can't be parsed.
+pub(super) fn lines(source: &str) -> Option<HashMap<String, usize>> {
+    let mut locator = Locator::default();
+    Parser::new_from_str(source)
+        .load(&mut locator, false)
+        .ok()?;
+    Some(locator.lines)
+}
+
+/// A collection being walked by the [`Locator`].
+enum Frame {
+    /// A mapping, with the key of the value being parsed.
+    Mapping(Option<String>),
+    /// A sequence, with the index of the item being parsed.
+    Sequence(usize),
+}
+
+/// Records the line of every path in a YAML document.
+#[derive(Default)]
+struct Locator {
+    stack: Vec<Frame>,
+    lines: HashMap<String, usize>,
+}
+
+impl Locator {
+    /// Returns the path of the node being parsed.
+    fn path(&self) -> String {
+        let mut path = String::new();
+        for frame in &self.stack {
+            match frame {
+                Frame::Mapping(Some(key)) => {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(key);
+                },
+                Frame::Mapping(None) => {},
+                Frame::Sequence(index) => path.push_str(&format!("[{index}]")),
+            }
+        }
+        path
+    }
+
+    /// Moves on to the next entry of the current collection.
+    fn next_entry(&mut self) {
+        match self.stack.last_mut() {
+            Some(Frame::Mapping(key)) => *key = None,
+            Some(Frame::Sequence(index)) => *index += 1,
+            None => {},
+        }
+    }
+}
+
+impl MarkedEventReceiver for Locator {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        // A scalar in a mapping without a current key is the key of the next value.
+        if let (Event::Scalar(key, ..), Some(Frame::Mapping(current @ None))) =
+            (&event, self.stack.last_mut())
+        {
+            *current = Some(key.clone());
+            let path = self.path();
+            self.lines.insert(path, mark.line());
+            return;
+        }
+
+        match event {
+            Event::Scalar(..) | Event::Alias(_) => {
+                self.lines.entry(self.path()).or_insert(mark.line());
+                self.next_entry();
+            },
+            Event::MappingStart(..) => {
+                self.lines.entry(self.path()).or_insert(mark.line());
+                self.stack.push(Frame::Mapping(None));
+            },
+            Event::SequenceStart(..) => {
+                self.lines.entry(self.path()).or_insert(mark.line());
+                self.stack.push(Frame::Sequence(0));
+            },
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.stack.pop();
+                self.next_entry();
+            },
+            _ => {},
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"roles:
+  devforum_member: "1"
+  devforum_regular: "1"
+  cooldown_bypass:
+    - "2"
+    - "1"
+
+faq_options:
+  - label: "First"
+    value: "same"
+    response:
+      content: "First answer"
+  - label: "Second"
+    value: "same"
+    response: {}
+"#;
+
+    fn errors() -> Vec<ConfigError> {
+        let cfg: Config = serde_yaml::from_str(SOURCE).unwrap();
+        let mut errors = validate(&cfg);
+        locate(&mut errors, SOURCE, false);
+        errors
+    }
+
+    fn messages() -> Vec<String> {
+        errors().iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn duplicate_roles() {
+        let messages = messages();
+        assert!(messages.contains(
+            &"roles.devforum_regular (line 3): role `1` is also used by roles.devforum_member"
+                .to_string()
+        ));
+        assert!(messages.contains(
+            &"roles.cooldown_bypass[1] (line 6): role `1` is also used by roles.devforum_member"
+                .to_string()
+        ));
+    }
+
+    #[test]
+    fn duplicate_faq_values() {
+        assert!(messages().contains(
+            &"faq_options[1].value (line 14): duplicate FAQ value `same`, also used by faq_options[0]"
+                .to_string()
+        ));
+    }
+
+    #[test]
+    fn empty_response() {
+        assert!(messages().contains(
+            &"faq_options[1].response (line 15): must have content, embeds or components"
+                .to_string()
+        ));
+        assert_eq!(errors().len(), 4);
+    }
+
+    #[test]
+    fn translated_messages() {
+        let error = &errors()[0];
+        assert_eq!(
+            error.message(&Locale::French),
+            "roles.devforum_regular (ligne 3) : le rôle `1` est aussi utilisé par roles.devforum_member"
+        );
+    }
+
+    #[test]
+    fn locates_closest_parent() {
+        let mut errors = vec![ConfigError::invalid("faq_options[0].response.embeds", "")];
+        locate(&mut errors, SOURCE, false);
+
+        assert_eq!(errors[0].line, Some(11));
+
+        let mut errors = vec![ConfigError::invalid("faq_options[0].response.embeds", "")];
+        locate(&mut errors, SOURCE, true);
+        assert_eq!(errors[0].line, None);
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use builders::locale::Locale;
+use twilight_model::application::interaction::Interaction;
+
+use crate::config::RoleConfig;
+use crate::error::InteractionError;
+use crate::i18n;
+
+/// What a cooldown is shared between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(dead_code)]
+pub(crate) enum Bucket {
+    /// Each user has their own cooldown.
+    User,
+    /// Users share the cooldown within a channel.
+    Channel,
+    /// Users share the cooldown within a server.
+    Guild,
+}
+
+impl Bucket {
+    /// Returns the ID identifying the bucket of the interaction,
+    /// or [`None`] if the interaction doesn't belong to such a bucket.
+    fn key(self, interaction: &Interaction) -> Option<u64> {
+        match self {
+            Bucket::User => interaction.author_id().map(|id| id.get()),
+            Bucket::Channel => interaction.channel.as_ref().map(|channel| channel.id.get()),
+            Bucket::Guild => interaction.guild_id.map(|id| id.get()),
+        }
+    }
+}
+
+/// A cooldown declared by a handler.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Cooldown {
+    bucket: Bucket,
+    duration: Duration,
+}
+
+impl Cooldown {
+    /// Create a cooldown of the given duration, shared within the given bucket.
+    pub(crate) const fn new(bucket: Bucket, duration: Duration) -> Self {
+        Self { bucket, duration }
+    }
+}
+
+/// Tracks the cooldowns of all handlers.
+#[derive(Debug, Default)]
+pub(crate) struct Cooldowns {
+    /// When the cooldown of each handler and bucket ends.
+    expires: Mutex<HashMap<(String, Bucket, u64), Instant>>,
+}
+
+impl Cooldowns {
+    /// Checks whether the handler is on cooldown for the interaction,
+    /// starting the cooldown if it isn't.
+    ///
+    /// Members with one of the configured bypass roles are never on cooldown.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [user error](InteractionError::User) telling the
+    /// user when they can try again if the handler is on cooldown.
+    pub(crate) fn check(
+        &self,
+        handler: &str,
+        cooldown: Cooldown,
+        interaction: &Interaction,
+        roles: &RoleConfig,
+        locale: &Locale,
+    ) -> Result<(), InteractionError> {
+        let bypass = interaction.member.as_ref().is_some_and(|member| {
+            member
+                .roles
+                .iter()
+                .any(|role_id| roles.cooldown_bypass.contains(role_id))
+        });
+        if bypass {
+            return Ok(());
+        }
+
+        let Some(key) = cooldown.bucket.key(interaction) else {
+            return Ok(());
+        };
+
+        match self.hit(handler, cooldown, key, Instant::now()) {
+            Some(remaining) => Err(InteractionError::user(i18n::message(
+                locale,
+                "cooldown",
+                &[("seconds", &remaining.as_secs_f64().ceil())],
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the remaining time if the bucket is on cooldown,
+    /// otherwise starts the cooldown and returns [`None`].
+    fn hit(&self, handler: &str, cooldown: Cooldown, key: u64, now: Instant) -> Option<Duration> {
+        let mut expires = self.expires.lock().expect("cooldowns lock poisoned");
+        let entry = (handler.to_string(), cooldown.bucket, key);
+
+        if let Some(remaining) = expires
+            .get(&entry)
+            .and_then(|expires| expires.checked_duration_since(now))
+            .filter(|remaining| !remaining.is_zero())
+        {
+            return Some(remaining);
+        }
+
+        // Drop the expired cooldowns so the map doesn't grow indefinitely
+        expires.retain(|_, expires| *expires > now);
+        expires.insert(entry, now + cooldown.duration);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use twilight_model::id::Id;
+
+    use super::*;
+    use crate::testing::{Simulation, USER_ID};
+
+    const COOLDOWN: Cooldown = Cooldown::new(Bucket::User, Duration::from_secs(10));
+
+    #[test]
+    fn on_cooldown() {
+        let cooldowns = Cooldowns::default();
+        let now = Instant::now();
+
+        assert_eq!(cooldowns.hit("faq", COOLDOWN, 1, now), None);
+        assert_eq!(
+            cooldowns.hit("faq", COOLDOWN, 1, now + Duration::from_secs(4)),
+            Some(Duration::from_secs(6))
+        );
+        assert_eq!(
+            cooldowns.hit("faq", COOLDOWN, 1, now + Duration::from_secs(10)),
+            None
+        );
+    }
+
+    #[test]
+    fn separate_buckets() {
+        let cooldowns = Cooldowns::default();
+        let now = Instant::now();
+
+        assert_eq!(cooldowns.hit("faq", COOLDOWN, 1, now), None);
+        assert_eq!(cooldowns.hit("faq", COOLDOWN, 2, now), None);
+        assert_eq!(cooldowns.hit("config", COOLDOWN, 1, now), None);
+        assert!(cooldowns.hit("faq", COOLDOWN, 1, now).is_some());
+    }
+
+    #[test]
+    fn expired_entries_are_dropped() {
+        let cooldowns = Cooldowns::default();
+        let now = Instant::now();
+
+        cooldowns.hit("faq", COOLDOWN, 1, now);
+        cooldowns.hit("faq", COOLDOWN, 2, now + Duration::from_secs(11));
+
+        assert_eq!(cooldowns.expires.lock().unwrap().len(), 1);
+    }
+
+    /// Returns interactions of two different users in the same channel and server.
+    async fn two_users() -> (Interaction, Interaction) {
+        let sim = Simulation::new().await;
+        let first = sim.command("faq", json!([]));
+        let mut second = first.clone();
+        let user = second
+            .member
+            .as_mut()
+            .and_then(|member| member.user.as_mut());
+        user.unwrap().id = Id::new(USER_ID + 1);
+        (first, second)
+    }
+
+    fn check(cooldowns: &Cooldowns, bucket: Bucket, interaction: &Interaction) -> bool {
+        let roles = RoleConfig {
+            devforum_member: Id::new(1),
+            devforum_regular: Id::new(2),
+            roblox_verified: None,
+            cooldown_bypass: Vec::new(),
+        };
+        let cooldown = Cooldown::new(bucket, Duration::from_secs(10));
+        cooldowns
+            .check("faq", cooldown, interaction, &roles, &i18n::FALLBACK)
+            .is_ok()
+    }
+
+    #[tokio::test]
+    async fn user_bucket() {
+        let (first, second) = two_users().await;
+        let cooldowns = Cooldowns::default();
+
+        assert!(check(&cooldowns, Bucket::User, &first));
+        assert!(check(&cooldowns, Bucket::User, &second));
+        assert!(!check(&cooldowns, Bucket::User, &first));
+    }
+
+    #[tokio::test]
+    async fn channel_bucket() {
+        let (first, second) = two_users().await;
+        let cooldowns = Cooldowns::default();
+
+        assert!(check(&cooldowns, Bucket::Channel, &first));
+        assert!(!check(&cooldowns, Bucket::Channel, &second));
+    }
+
+    #[tokio::test]
+    async fn guild_bucket() {
+        let (first, mut second) = two_users().await;
+        // Users in different channels of the server still share the cooldown
+        second.channel.as_mut().unwrap().id = Id::new(1);
+        let cooldowns = Cooldowns::default();
+
+        assert!(check(&cooldowns, Bucket::Guild, &first));
+        assert!(!check(&cooldowns, Bucket::Guild, &second));
+    }
+}
@@ -0,0 +1,253 @@
+//! Verifies the DevForum trust level of members and updates their roles to match it.
+//!
+//! The outcome is returned as a [`Verification`], which each caller renders in its own way.
+
+use anyhow::Context;
+use twilight_model::id::marker::{GuildMarker, RoleMarker, UserMarker};
+use twilight_model::id::Id;
+
+use crate::api::{DevForumProfile, DevForumTrustLevel};
+use crate::config::RoleConfig;
+
+/// The outcome of verifying the DevForum trust level of a member.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Verification {
+    /// The member isn't linked to a Roblox account with RoVer.
+    NotLinked,
+    /// The Roblox account linked with RoVer couldn't be fetched.
+    RoVerLookupFailed,
+    /// The username of the Roblox account couldn't be fetched.
+    RobloxLookupFailed,
+    /// The DevForum profile of the Roblox account is hidden.
+    ProfileHidden,
+    /// The Roblox account doesn't have a DevForum account.
+    NoDevForumAccount,
+    /// The DevForum profile of the Roblox account couldn't be fetched.
+    DevForumLookupFailed,
+    /// The roles of the member were updated to match their trust level.
+    RolesUpdated {
+        trust_level: DevForumTrustLevel,
+        update: RoleUpdate,
+    },
+    /// The roles of the member already matched their trust level.
+    RolesUnchanged { trust_level: DevForumTrustLevel },
+    /// The roles of the member couldn't be fetched or updated on Discord.
+    RoleUpdateFailed { trust_level: DevForumTrustLevel },
+}
+
+impl Verification {
+    /// Returns the label of the outcome in the metrics.
+    fn outcome(&self) -> &'static str {
+        match self {
+            Verification::NotLinked => "not_linked",
+            Verification::RoVerLookupFailed => "rover_failed",
+            Verification::RobloxLookupFailed => "roblox_failed",
+            Verification::ProfileHidden => "profile_hidden",
+            Verification::NoDevForumAccount => "no_devforum_account",
+            Verification::DevForumLookupFailed => "devforum_failed",
+            Verification::RolesUpdated { .. } => "updated",
+            Verification::RolesUnchanged { .. } => "unchanged",
+            Verification::RoleUpdateFailed { .. } => "update_failed",
+        }
+    }
+
+    /// Returns the trust level of the member, if it was fetched.
+    pub(crate) fn trust_level(&self) -> Option<DevForumTrustLevel> {
+        match self {
+            Verification::RolesUpdated { trust_level, .. }
+            | Verification::RolesUnchanged { trust_level }
+            | Verification::RoleUpdateFailed { trust_level } => Some(*trust_level),
+            _ => None,
+        }
+    }
+}
+
+/// The DevForum roles added to and removed from a member.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct RoleUpdate {
+    pub(crate) added: Vec<Id<RoleMarker>>,
+    pub(crate) removed: Vec<Id<RoleMarker>>,
+}
+
+impl RoleUpdate {
+    /// Returns the roles to add and remove for the member roles to match the trust level.
+    fn new(
+        trust_level: DevForumTrustLevel,
+        roles: &RoleConfig,
+        member_roles: &[Id<RoleMarker>],
+    ) -> Self {
+        let (add, remove) = match trust_level {
+            DevForumTrustLevel::Visitor => {
+                (None, vec![roles.devforum_member, roles.devforum_regular])
+            },
+            DevForumTrustLevel::Member => {
+                (Some(roles.devforum_member), vec![roles.devforum_regular])
+            },
+            DevForumTrustLevel::Regular | DevForumTrustLevel::Staff => {
+                (Some(roles.devforum_regular), vec![roles.devforum_member])
+            },
+        };
+
+        RoleUpdate {
+            added: add
+                .filter(|role| !member_roles.contains(role))
+                .into_iter()
+                .collect(),
+            removed: member_roles
+                .iter()
+                .copied()
+                .filter(|role| remove.contains(role))
+                .collect(),
+        }
+    }
+
+    /// Returns whether any role is added or removed.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// Returns a line per added and removed role, in the locale of the user.
+    pub(crate) fn lines(&self, ctx: &crate::Context) -> String {
+        let added = self
+            .added
+            .iter()
+            .map(|role| ctx.message("devforum.added", &[("role", role)]));
+        let removed = self
+            .removed
+            .iter()
+            .map(|role| ctx.message("devforum.removed", &[("role", role)]));
+        added.chain(removed).collect::<Vec<_>>().join("\n")
+    }
+
+    /// Returns the member roles with the update applied.
+    fn apply(&self, member_roles: &[Id<RoleMarker>]) -> Vec<Id<RoleMarker>> {
+        member_roles
+            .iter()
+            .filter(|role| !self.removed.contains(role))
+            .chain(&self.added)
+            .copied()
+            .collect()
+    }
+}
+
+/// Verifies the DevForum trust level of the member and updates their roles to match it.
+///
+/// The failures of the external APIs and of Discord are [outcomes](Verification) too.
+pub(crate) async fn verify(
+    ctx: &crate::Context,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+) -> Verification {
+    let verification = verify_member(ctx, guild_id, user_id).await;
+    crate::metrics::verification(
+        verification.outcome(),
+        verification.trust_level().map(|level| level.to_string()),
+    );
+    verification
+}
+
+async fn verify_member(
+    ctx: &crate::Context,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+) -> Verification {
+    // Get the user's Roblox ID using their Discord ID from the RoVer verification API.
+    let rover_data = match ctx.api.fetch_rover_data(guild_id, user_id).await {
+        Ok(Some(data)) => data,
+        Ok(None) => return Verification::NotLinked,
+        Err(error) => {
+            tracing::warn!(?error, "fetch rover data");
+            return Verification::RoVerLookupFailed;
+        },
+    };
+
+    // Get the user's Roblox username using their Roblox ID from the Roblox API.
+    let roblox_data = match ctx.api.fetch_roblox_data(rover_data.roblox_id).await {
+        Ok(data) => data,
+        Err(error) => {
+            tracing::warn!(?error, "fetch roblox data");
+            return Verification::RobloxLookupFailed;
+        },
+    };
+
+    // Get the user's trust level using their Roblox username from the DevForum API.
+    let trust_level = match ctx.api.fetch_devforum_data(&roblox_data.name).await {
+        Ok(DevForumProfile::Visible(data)) => data.user.trust_level,
+        Ok(DevForumProfile::Hidden) => return Verification::ProfileHidden,
+        Ok(DevForumProfile::Missing) => return Verification::NoDevForumAccount,
+        Err(error) => {
+            tracing::warn!(?error, "fetch devforum data");
+            return Verification::DevForumLookupFailed;
+        },
+    };
+
+    // Update the user's roles in the Discord server based on their trust level.
+    match update_roles(ctx, guild_id, user_id, trust_level).await {
+        Ok(update) if update.is_empty() => Verification::RolesUnchanged { trust_level },
+        Ok(update) => Verification::RolesUpdated {
+            trust_level,
+            update,
+        },
+        Err(error) => {
+            tracing::warn!(?error, "update devforum roles");
+            Verification::RoleUpdateFailed { trust_level }
+        },
+    }
+}
+
+/// Updates the roles of the member to match the trust level, returning the update.
+async fn update_roles(
+    ctx: &crate::Context,
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    trust_level: DevForumTrustLevel,
+) -> anyhow::Result<RoleUpdate> {
+    let member_roles =
+        crate::metrics::discord("guild_member", ctx.http.guild_member(guild_id, user_id))
+            .await
+            .context("get guild member")?
+            .model()
+            .await
+            .context("deserialize guild member")?
+            .roles;
+    let update = RoleUpdate::new(trust_level, &ctx.cfg().roles, &member_roles);
+    if update.is_empty() {
+        return Ok(update);
+    }
+
+    crate::metrics::discord(
+        "update_guild_member",
+        ctx.http
+            .update_guild_member(guild_id, user_id)
+            .roles(&update.apply(&member_roles)),
+    )
+    .await
+    .context("update guild member roles")?;
+
+    Ok(update)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roles() -> RoleConfig {
+        serde_yaml::from_str(r#"{ devforum_member: "1", devforum_regular: "2" }"#).unwrap()
+    }
+
+    #[test]
+    fn role_updates() {
+        let member_roles = [Id::new(1), Id::new(3)];
+
+        let update = RoleUpdate::new(DevForumTrustLevel::Regular, &roles(), &member_roles);
+        assert_eq!(update.added, [Id::new(2)]);
+        assert_eq!(update.removed, [Id::new(1)]);
+        assert_eq!(update.apply(&member_roles), [Id::new(3), Id::new(2)]);
+
+        let update = RoleUpdate::new(DevForumTrustLevel::Visitor, &roles(), &member_roles);
+        assert!(update.added.is_empty());
+        assert_eq!(update.removed, [Id::new(1)]);
+
+        assert!(RoleUpdate::new(DevForumTrustLevel::Member, &roles(), &member_roles).is_empty());
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt::Display;
+
+use anyhow::Context;
+
+/// Error returned by interaction handlers.
+///
+/// Handlers return [`anyhow::Error`], so this error is wrapped in it and recovered
+/// by the dispatcher through [`InteractionError::from`]. Any other error is
+/// considered [internal](InteractionError::Internal).
+#[derive(Debug)]
+pub(crate) enum InteractionError {
+    /// The interaction can't be completed because of the user's input.
+    /// The message is shown to the user as is.
+    User(String),
+    /// The user isn't allowed to use the interaction.
+    /// The message is shown to the user as is.
+    Permission(String),
+    /// An unexpected error, the details are only logged.
+    Internal(anyhow::Error),
+}
+
+impl InteractionError {
+    /// Creates a [user error](InteractionError::User).
+    pub(crate) fn user(message: impl Into<String>) -> Self {
+        InteractionError::User(message.into())
+    }
+
+    /// Creates a [permission error](InteractionError::Permission).
+    pub(crate) fn permission(message: impl Into<String>) -> Self {
+        InteractionError::Permission(message.into())
+    }
+
+    /// Logs the error and responds to the interaction with an ephemeral message
+    /// containing a correlation ID, which can be used to find the log entry.
+    pub(crate) async fn respond(&self, ctx: &crate::Context) -> anyhow::Result<()> {
+        let responder = ctx.responder()?;
+        let correlation_id = responder.correlation_id();
+        let message = match self {
+            InteractionError::User(message) => {
+                tracing::debug!(correlation_id, %message, "user error handling interaction");
+                message.clone()
+            },
+            InteractionError::Permission(message) => {
+                tracing::info!(correlation_id, %message, "permission error handling interaction");
+                message.clone()
+            },
+            InteractionError::Internal(error) => {
+                tracing::error!(
+                    correlation_id,
+                    ?error,
+                    "internal error handling interaction"
+                );
+                ctx.message("errors.internal", &[])
+            },
+        };
+        let content = format!(
+            "{message}\n{}",
+            ctx.message("errors.id", &[("id", &correlation_id)])
+        );
+
+        responder
+            .reply_ephemeral(content)
+            .await
+            .context("respond with error message")
+    }
+}
+
+impl Display for InteractionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InteractionError::User(message) => write!(f, "user error: {message}"),
+            InteractionError::Permission(message) => write!(f, "permission error: {message}"),
+            InteractionError::Internal(error) => write!(f, "internal error: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for InteractionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InteractionError::Internal(error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<anyhow::Error> for InteractionError {
+    fn from(error: anyhow::Error) -> Self {
+        error
+            .downcast::<InteractionError>()
+            .unwrap_or_else(InteractionError::Internal)
+    }
+}
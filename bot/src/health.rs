@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use metrics_exporter_prometheus::PrometheusHandle;
+use serde::Serialize;
+use twilight_gateway::ShardId;
+
+use crate::config::live::LiveConfig;
+
+/// The connection state of a shard.
+#[derive(Debug, Clone, Default, Serialize)]
+struct ShardHealth {
+    /// Whether the shard has identified or resumed and hasn't been disconnected since.
+    connected: bool,
+    /// The average heartbeat latency of the shard in milliseconds.
+    latency_ms: Option<u128>,
+    /// When the shard last received an event, in milliseconds since the Unix epoch.
+    last_event_ms: Option<u128>,
+}
+
+/// Tracks the state reported by the health and readiness endpoints.
+#[derive(Debug)]
+pub(crate) struct Health {
+    started: Instant,
+    shards: Mutex<BTreeMap<u32, ShardHealth>>,
+    commands_published: AtomicBool,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            shards: Mutex::default(),
+            commands_published: AtomicBool::new(false),
+        }
+    }
+}
+
+/// The body of the `/readyz` response.
+#[derive(Debug, Serialize)]
+struct Readiness {
+    ready: bool,
+    config_loaded: bool,
+    commands_published: bool,
+    shards: BTreeMap<u32, ShardHealth>,
+}
+
+impl Health {
+    /// Start tracking the given shard, which is disconnected until it's ready.
+    pub(crate) fn register_shard(&self, shard_id: ShardId) {
+        self.shards
+            .lock()
+            .expect("health lock poisoned")
+            .entry(shard_id.number())
+            .or_default();
+    }
+
+    /// Records an event received by the shard, along with its current latency.
+    ///
+    /// `connected` is [`None`] if the event doesn't change the connection state.
+    pub(crate) fn record_event(
+        &self,
+        shard_id: ShardId,
+        connected: Option<bool>,
+        latency: Option<Duration>,
+    ) {
+        let mut shards = self.shards.lock().expect("health lock poisoned");
+        let shard = shards.entry(shard_id.number()).or_default();
+
+        if let Some(connected) = connected {
+            shard.connected = connected;
+        }
+        shard.latency_ms = latency.map(|latency| latency.as_millis());
+        shard.last_event_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|since| since.as_millis());
+    }
+
+    /// Marks the global commands as published.
+    pub(crate) fn commands_published(&self) {
+        self.commands_published.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns the readiness of the bot, given whether its config file is loaded.
+    fn readiness(&self, config_loaded: bool) -> Readiness {
+        let shards = self.shards.lock().expect("health lock poisoned").clone();
+        let commands_published = self.commands_published.load(Ordering::Relaxed);
+
+        Readiness {
+            ready: config_loaded
+                && commands_published
+                && !shards.is_empty()
+                && shards.values().all(|shard| shard.connected),
+            config_loaded,
+            commands_published,
+            shards,
+        }
+    }
+}
+
+/// Serves the health, readiness and metrics endpoints on the given address.
+pub(crate) async fn serve(
+    address: SocketAddr,
+    health: Arc<Health>,
+    config: Arc<LiveConfig>,
+    metrics: PrometheusHandle,
+) -> anyhow::Result<()> {
+    let app = Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .with_state((health, config))
+        .route("/metrics", get(move || async move { metrics.render() }));
+
+    let listener = tokio::net::TcpListener::bind(address)
+        .await
+        .with_context(|| format!("bind health server to {address}"))?;
+    tracing::info!(%address, "serving health checks");
+
+    axum::serve(listener, app)
+        .await
+        .context("serve health checks")
+}
+
+/// Responds as long as the process is alive.
+async fn healthz(
+    State((health, _)): State<(Arc<Health>, Arc<LiveConfig>)>,
+) -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "status": "ok",
+        "uptime_secs": health.started.elapsed().as_secs(),
+    }))
+}
+
+/// Responds with `503 Service Unavailable` until the bot is ready to handle interactions.
+async fn readyz(
+    State((health, config)): State<(Arc<Health>, Arc<LiveConfig>)>,
+) -> (StatusCode, Json<Readiness>) {
+    let readiness = health.readiness(config.loaded());
+    let status = if readiness.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(readiness))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ready_once_connected_and_published() {
+        let health = Health::default();
+        assert!(!health.readiness(true).ready);
+
+        health.register_shard(ShardId::new(0, 2));
+        health.register_shard(ShardId::new(1, 2));
+        health.record_event(ShardId::new(0, 2), Some(true), None);
+        health.commands_published();
+        assert!(!health.readiness(true).ready);
+
+        health.record_event(ShardId::new(1, 2), Some(true), None);
+        assert!(health.readiness(true).ready);
+        // A config file that failed to be written back makes the bot unready
+        assert!(!health.readiness(false).ready);
+        assert!(!health.readiness(false).config_loaded);
+
+        health.record_event(ShardId::new(1, 2), Some(false), None);
+        assert!(!health.readiness(true).ready);
+    }
+
+    #[test]
+    fn records_latency_and_last_event() {
+        let health = Health::default();
+        health.record_event(ShardId::ONE, None, Some(Duration::from_millis(42)));
+
+        let shard = &health.readiness(true).shards[&0];
+        assert!(!shard.connected);
+        assert_eq!(shard.latency_ms, Some(42));
+        assert!(shard.last_event_ms.is_some());
+    }
+}
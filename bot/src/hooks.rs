@@ -0,0 +1,212 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use twilight_model::application::interaction::Interaction;
+
+use crate::access::Access;
+use crate::cooldown::Cooldown;
+
+/// Information about the interaction being dispatched, passed to each hook.
+pub(crate) struct HookContext<'a> {
+    /// The interaction being dispatched.
+    pub(crate) interaction: &'a Interaction,
+    /// The name of the command or custom ID prefix of the component.
+    pub(crate) handler: &'a str,
+    /// The access requirements declared by the handler.
+    pub(crate) access: Access,
+    /// The cooldown declared by the handler.
+    pub(crate) cooldown: Option<Cooldown>,
+    /// When the dispatch started.
+    pub(crate) started: Instant,
+}
+
+impl<'a> HookContext<'a> {
+    /// Create the hook context for the given interaction and handler declarations.
+    pub(crate) fn new(
+        interaction: &'a Interaction,
+        handler: &'a str,
+        access: Access,
+        cooldown: Option<Cooldown>,
+    ) -> Self {
+        Self {
+            interaction,
+            handler,
+            access,
+            cooldown,
+            started: Instant::now(),
+        }
+    }
+}
+
+/// Trait for implementing logic that runs around every interaction handler.
+#[async_trait]
+pub(crate) trait Hook: Send + Sync {
+    /// Runs before the handler, returning an error prevents the handler
+    /// and the remaining `before` hooks from running.
+    async fn before(
+        &self,
+        _hook_ctx: &HookContext<'_>,
+        _ctx: &crate::Context,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Runs after the handler with its result, or with the error of the `before` hook
+    /// that prevented it from running. Runs even if the handler didn't.
+    async fn after(
+        &self,
+        _hook_ctx: &HookContext<'_>,
+        _ctx: &crate::Context,
+        _result: &anyhow::Result<()>,
+    ) {
+    }
+}
+
+/// An ordered chain of hooks.
+///
+/// The `before` hooks run in the order they were added,
+/// and the `after` hooks run in the reverse order.
+#[derive(Clone)]
+pub(crate) struct Hooks(Vec<Arc<dyn Hook>>);
+
+impl Default for Hooks {
+    /// The hooks that run around every handler: logging, metrics, access and cooldown checks.
+    fn default() -> Self {
+        Self(Vec::new())
+            .with(LoggingHook)
+            .with(MetricsHook)
+            .with(AccessHook)
+            .with(CooldownHook)
+    }
+}
+
+impl Hooks {
+    /// Add a hook to the end of the chain.
+    pub(crate) fn with(mut self, hook: impl Hook + 'static) -> Self {
+        self.0.push(Arc::new(hook));
+        self
+    }
+
+    /// Runs the handler wrapped in the hook chain.
+    pub(crate) async fn run<F>(
+        &self,
+        hook_ctx: &HookContext<'_>,
+        ctx: &crate::Context,
+        handler: F,
+    ) -> anyhow::Result<()>
+    where
+        F: Future<Output = anyhow::Result<()>>,
+    {
+        let mut result = Ok(());
+        for hook in &self.0 {
+            result = hook.before(hook_ctx, ctx).await;
+            if result.is_err() {
+                break;
+            }
+        }
+
+        if result.is_ok() {
+            result = handler.await;
+        }
+
+        for hook in self.0.iter().rev() {
+            hook.after(hook_ctx, ctx, &result).await;
+        }
+
+        result
+    }
+}
+
+/// Records the handler on the dispatch span, and logs
+/// the outcome and duration of every handler.
+struct LoggingHook;
+
+#[async_trait]
+impl Hook for LoggingHook {
+    async fn before(
+        &self,
+        hook_ctx: &HookContext<'_>,
+        _ctx: &crate::Context,
+    ) -> anyhow::Result<()> {
+        tracing::Span::current().record("handler", hook_ctx.handler);
+        Ok(())
+    }
+
+    async fn after(
+        &self,
+        hook_ctx: &HookContext<'_>,
+        _ctx: &crate::Context,
+        result: &anyhow::Result<()>,
+    ) {
+        let elapsed_ms = hook_ctx.started.elapsed().as_millis();
+        match result {
+            Ok(()) => tracing::info!(
+                handler = hook_ctx.handler,
+                elapsed_ms,
+                "handled interaction"
+            ),
+            Err(error) => tracing::warn!(
+                handler = hook_ctx.handler,
+                elapsed_ms,
+                %error,
+                "failed to handle interaction"
+            ),
+        }
+    }
+}
+
+/// Records the outcome and duration of every handler.
+struct MetricsHook;
+
+#[async_trait]
+impl Hook for MetricsHook {
+    async fn after(
+        &self,
+        hook_ctx: &HookContext<'_>,
+        _ctx: &crate::Context,
+        result: &anyhow::Result<()>,
+    ) {
+        crate::metrics::interaction(hook_ctx.handler, result, hook_ctx.started.elapsed());
+    }
+}
+
+/// Enforces the [access requirements](Access) declared by the handler.
+struct AccessHook;
+
+#[async_trait]
+impl Hook for AccessHook {
+    async fn before(&self, hook_ctx: &HookContext<'_>, ctx: &crate::Context) -> anyhow::Result<()> {
+        hook_ctx
+            .access
+            .check(
+                hook_ctx.interaction.member.as_ref(),
+                &ctx.cfg().roles,
+                &ctx.locale,
+            )
+            .map_err(Into::into)
+    }
+}
+
+/// Enforces the [cooldown](Cooldown) declared by the handler.
+struct CooldownHook;
+
+#[async_trait]
+impl Hook for CooldownHook {
+    async fn before(&self, hook_ctx: &HookContext<'_>, ctx: &crate::Context) -> anyhow::Result<()> {
+        let Some(cooldown) = hook_ctx.cooldown else {
+            return Ok(());
+        };
+
+        ctx.cooldowns
+            .check(
+                hook_ctx.handler,
+                cooldown,
+                hook_ctx.interaction,
+                &ctx.cfg().roles,
+                &ctx.locale,
+            )
+            .map_err(Into::into)
+    }
+}
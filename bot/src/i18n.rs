@@ -0,0 +1,195 @@
+//! Translates the messages shown to users, using a catalogue per locale.
+//!
+//! The catalogues are the YAML files in `locales`, named after the code of their
+//! locale and embedded at build time. Nested keys are joined with dots.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::LazyLock;
+
+use builders::locale::Locale;
+use serde_yaml::Value;
+
+/// The locale of the reference catalogue, used for the messages
+/// that aren't translated to the locale of the user.
+pub(crate) const FALLBACK: Locale = Locale::EnglishUS;
+
+/// The source of every catalogue, by locale code.
+const SOURCES: [(&str, &str); 2] = [
+    ("en-US", include_str!("../locales/en-US.yml")),
+    ("fr", include_str!("../locales/fr.yml")),
+];
+
+/// The messages of every catalogue, by locale code and key.
+static CATALOGUES: LazyLock<HashMap<&str, HashMap<String, String>>> = LazyLock::new(|| {
+    SOURCES
+        .into_iter()
+        .map(|(code, source)| {
+            let document = serde_yaml::from_str(source)
+                .unwrap_or_else(|error| panic!("invalid {code} catalogue: {error}"));
+            let mut messages = HashMap::new();
+            flatten(String::new(), document, &mut messages);
+            (code, messages)
+        })
+        .collect()
+});
+
+/// Adds the messages of the value to the catalogue, prefixing their keys.
+fn flatten(prefix: String, value: Value, messages: &mut HashMap<String, String>) {
+    match value {
+        Value::Mapping(mapping) => {
+            for (key, value) in mapping {
+                let key = key.as_str().expect("catalogue keys are strings");
+                let key = if prefix.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten(key, value, messages);
+            }
+        },
+        Value::String(message) => {
+            messages.insert(prefix, message);
+        },
+        value => panic!("expected a message at `{prefix}`, found {value:?}"),
+    }
+}
+
+/// Returns the locale of the given code, such as the locale of an interaction,
+/// or the [fallback](FALLBACK) if there's none.
+pub(crate) fn locale(code: Option<&str>) -> Locale {
+    code.map_or(FALLBACK, |code| code.parse().unwrap_or(FALLBACK))
+}
+
+/// Returns the message with the given key in the locale, replacing its `{name}` arguments.
+///
+/// The message is looked up in the catalogue of the locale, then in the catalogues of
+/// the other variants of its language in the order of [`SOURCES`] and lastly in the
+/// [fallback](FALLBACK) catalogue.
+///
+/// The arguments are replaced in a single pass, so their values are never substituted.
+pub(crate) fn message(locale: &Locale, key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let code = locale.to_string();
+    let code_language = language(&code);
+    let fallback = FALLBACK.to_string();
+
+    let found = CATALOGUES
+        .get(code.as_str())
+        .and_then(|messages| messages.get(key))
+        .or_else(|| {
+            SOURCES
+                .iter()
+                .filter(|(other, _)| language(other) == code_language)
+                .find_map(|(other, _)| CATALOGUES[other].get(key))
+        })
+        .or_else(|| CATALOGUES[fallback.as_str()].get(key));
+    let Some(message) = found else {
+        tracing::warn!(key, "missing message");
+        return key.to_string();
+    };
+
+    substitute(message, args)
+}
+
+/// Replaces the `{name}` arguments of the message, leaving the unknown ones as is.
+fn substitute(message: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut substituted = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(start) = rest.find('{') {
+        substituted.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let arg = rest.find('}').and_then(|end| {
+            let (_, value) = args.iter().find(|(name, _)| *name == &rest[1..end])?;
+            Some((end, value))
+        });
+        match arg {
+            Some((end, value)) => {
+                substituted.push_str(&value.to_string());
+                rest = &rest[end + 1..];
+            },
+            None => {
+                substituted.push('{');
+                rest = &rest[1..];
+            },
+        }
+    }
+    substituted.push_str(rest);
+    substituted
+}
+
+/// Returns the translations of the message with the given key,
+/// for the name and description localizations of commands.
+pub(crate) fn localizations(key: &str) -> Vec<(Locale, String)> {
+    let fallback = FALLBACK.to_string();
+    let mut localizations: Vec<(Locale, String)> = CATALOGUES
+        .iter()
+        .filter(|(code, _)| **code != fallback)
+        .filter_map(|(code, messages)| Some((locale(Some(code)), messages.get(key)?.clone())))
+        .collect();
+    // Keep the order stable so commands don't look changed when syncing.
+    localizations.sort_by_key(|(locale, _)| locale.to_string());
+    localizations
+}
+
+/// Returns the language of the locale code, such as `es` for `es-419`.
+fn language(code: &str) -> &str {
+    code.split('-').next().unwrap_or(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalogues_only_translate_reference_messages() {
+        let reference = &CATALOGUES[FALLBACK.to_string().as_str()];
+        for (code, messages) in CATALOGUES.iter() {
+            for key in messages.keys() {
+                // Names are localized next to the description of their command or option
+                let key = match key.strip_suffix(".name") {
+                    Some(described) => format!("{described}.description"),
+                    None => key.clone(),
+                };
+                assert!(
+                    reference.contains_key(&key),
+                    "`{key}` of the {code} catalogue isn't in the reference catalogue"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn falls_back_to_reference_catalogue() {
+        let args: &[(&str, &dyn Display)] = &[("seconds", &3)];
+        assert_eq!(
+            message(&Locale::French, "cooldown", args),
+            "Vous allez trop vite, réessayez dans 3 secondes."
+        );
+        assert_eq!(
+            message(&locale(Some("de")), "cooldown", args),
+            "You're doing that too fast, try again in 3 seconds."
+        );
+        assert_eq!(message(&FALLBACK, "missing.key", &[]), "missing.key");
+        assert_eq!(
+            message(&Locale::EnglishUK, "cooldown", args),
+            message(&FALLBACK, "cooldown", args)
+        );
+        assert_eq!(localizations("commands.faq.options.query.name"), [(
+            Locale::French,
+            "question".to_string()
+        )]);
+    }
+
+    #[test]
+    fn substitutes_arguments_once() {
+        assert_eq!(
+            message(&FALLBACK, "access.missing_role", &[("role", &"{role}")]),
+            "You must have the <@&{role}> role to use this interaction."
+        );
+        assert_eq!(
+            substitute("{a} {b} {c} {", &[("a", &"{b}"), ("b", &1)]),
+            "{b} 1 {c} {"
+        );
+    }
+}
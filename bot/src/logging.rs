@@ -0,0 +1,67 @@
+use anyhow::Context;
+use tracing::Subscriber;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt as _;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt as _;
+use tracing_subscriber::{EnvFilter, Layer};
+
+use crate::config::{LogFormat, LogRotation, LoggingConfig};
+
+/// The filter used when neither the config nor the `RUST_LOG` env set one.
+const DEFAULT_FILTER: &str = "info";
+
+/// Installs the global subscriber from the logging config.
+///
+/// Returns a guard that must be held until the process exits
+/// so that the buffered lines are written to the log file.
+pub(crate) fn init(cfg: &LoggingConfig) -> anyhow::Result<Option<WorkerGuard>> {
+    let filter = EnvFilter::try_new(cfg.filter.as_deref().unwrap_or(DEFAULT_FILTER))
+        .context("parse log filter")?;
+    let format = cfg.format;
+
+    let (file, guard) = match &cfg.file {
+        Some(file) => {
+            let rotation = match file.rotation {
+                LogRotation::Minutely => Rotation::MINUTELY,
+                LogRotation::Hourly => Rotation::HOURLY,
+                LogRotation::Daily => Rotation::DAILY,
+                LogRotation::Never => Rotation::NEVER,
+            };
+            let appender = RollingFileAppender::builder()
+                .rotation(rotation)
+                .filename_prefix(&file.prefix)
+                .build(&file.directory)
+                .with_context(|| format!("open log directory: {}", file.directory.display()))?;
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            (Some(layer(format, writer, false)), Some(guard))
+        },
+        None => (None, None),
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(layer(format, std::io::stdout, true))
+        .with(file)
+        .try_init()
+        .context("install tracing subscriber")?;
+
+    Ok(guard)
+}
+
+/// Creates a layer writing the log lines in the given format.
+fn layer<S, W>(format: LogFormat, writer: W, ansi: bool) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(ansi);
+    match format {
+        LogFormat::Text => layer.boxed(),
+        LogFormat::Json => layer.json().boxed(),
+    }
+}
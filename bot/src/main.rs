@@ -1,53 +1,222 @@
+mod access;
+mod api;
+mod cli;
 mod commands;
 mod components;
 mod config;
+mod cooldown;
+mod devforum;
+mod error;
+mod health;
+mod hooks;
+mod i18n;
+mod logging;
+mod metrics;
 mod modals;
+mod responder;
+#[cfg(test)]
+mod testing;
 
+use std::fmt::Display;
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Context as _;
+use builders::locale::Locale;
+use clap::Parser as _;
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+use tracing::Instrument as _;
 use twilight_cache_inmemory::{DefaultInMemoryCache, ResourceType};
-use twilight_gateway::{Event, EventTypeFlags, Intents, Shard, ShardId, StreamExt as _};
+use twilight_gateway::{
+    CloseFrame, Event, EventTypeFlags, Intents, MessageSender, Shard, StreamExt as _,
+};
 use twilight_http::Client as HttpClient;
-use twilight_model::application::interaction::InteractionData;
+use twilight_model::application::interaction::{Interaction, InteractionData, InteractionType};
 
-use crate::config::Config;
+use crate::api::{ApiUrls, ExternalApi, HttpApi};
+use crate::cli::{Cli, CliCommand, CommandsAction};
+use crate::config::live::LiveConfig;
+use crate::config::{Config, LoggingConfig};
+use crate::cooldown::Cooldowns;
+use crate::error::InteractionError;
+use crate::health::Health;
+use crate::hooks::Hooks;
+use crate::responder::Responder;
+
+/// The number of events that can be queued for the dispatcher.
+const EVENT_BUFFER: usize = 256;
+/// How long to wait for the shards to close and the in-flight events
+/// to be handled when shutting down.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(Clone)]
 pub(crate) struct Context {
     http: Arc<HttpClient>,
-    cfg: Arc<Config>,
-    request: Arc<reqwest::Client>,
+    config: Arc<LiveConfig>,
+    api: Arc<dyn ExternalApi>,
+    cooldowns: Arc<Cooldowns>,
+    hooks: Arc<Hooks>,
+    health: Arc<Health>,
+    /// The responder of the interaction being handled, if any.
+    responder: Option<Responder>,
+    /// The locale of the user of the interaction being handled.
+    locale: Locale,
+    /// The locale of the server of the interaction being handled.
+    guild_locale: Locale,
 }
 
-fn validate_config() -> anyhow::Result<()> {
-    let path = std::env::args()
-        .nth(2)
-        .unwrap_or_else(|| "magnolia.cfg.yml".to_string());
-    config::load_config(path)?;
-    Ok(())
+impl Context {
+    /// Creates the state from the given config, loaded from the given path.
+    fn new(token: String, cfg: Config, cfg_path: PathBuf) -> Self {
+        let urls = ApiUrls::default();
+        let api = HttpApi::new(urls.clone(), &cfg.secrets);
+
+        // HTTP is separate from the gateway, so create a new client.
+        let http = urls.discord_client(token);
+        Self::with_clients(http, LiveConfig::new(cfg, cfg_path), Arc::new(api))
+    }
+
+    /// Creates the state from the given config and clients.
+    fn with_clients(http: HttpClient, cfg: LiveConfig, api: Arc<dyn ExternalApi>) -> Self {
+        Context {
+            http: Arc::new(http),
+            config: Arc::new(cfg),
+            api,
+            cooldowns: Arc::new(Cooldowns::default()),
+            hooks: Arc::new(Hooks::default()),
+            health: Arc::new(Health::default()),
+            responder: None,
+            locale: i18n::FALLBACK,
+            guild_locale: i18n::FALLBACK,
+        }
+    }
+
+    /// Returns a copy of the state for handling the given interaction.
+    fn with_interaction(&self, interaction: &Interaction) -> Self {
+        Context {
+            responder: Some(Responder::new(self.http.clone(), interaction)),
+            locale: i18n::locale(interaction.locale.as_deref()),
+            guild_locale: i18n::locale(interaction.guild_locale.as_deref()),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the current config.
+    pub(crate) fn cfg(&self) -> Arc<Config> {
+        self.config.get()
+    }
+
+    /// Returns the responder of the interaction being handled.
+    pub(crate) fn responder(&self) -> anyhow::Result<&Responder> {
+        self.responder
+            .as_ref()
+            .context("get responder outside of an interaction")
+    }
+
+    /// Returns the [message](i18n::message) in the locale of the user,
+    /// for the responses only they can see.
+    pub(crate) fn message(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        i18n::message(&self.locale, key, args)
+    }
+
+    /// Returns the [message](i18n::message) in the locale of the server,
+    /// for the messages everyone in the channel can see.
+    pub(crate) fn guild_message(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        i18n::message(&self.guild_locale, key, args)
+    }
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Initialize the tracing subscriber.
-    tracing_subscriber::fmt::init();
-
-    // Validate the config file.
-    if let Some(arg) = std::env::args().nth(1) {
-        if arg == "validate-config" {
-            validate_config()?;
-            return Ok(());
-        }
+    let cli = Cli::parse();
+    let command = cli.command.unwrap_or(CliCommand::Run);
+
+    // Only the bot itself loads the config file,
+    // since the other commands may not have a valid one.
+    let cfg = match command {
+        CliCommand::Run => Some(config::load_config(&cli.config)?),
+        _ => None,
+    };
+
+    // Initialize the tracing subscriber, configured by the config file if it was loaded.
+    let _guard = match &cfg {
+        Some(cfg) => logging::init(&cfg.logging)?,
+        None => {
+            let mut logging = LoggingConfig::default();
+            config::env::apply_logging(&mut logging)?;
+            logging::init(&logging)?
+        },
+    };
+
+    match command {
+        CliCommand::Run => run(cfg.expect("config is loaded to run"), cli.config).await,
+        CliCommand::ValidateConfig { path } => {
+            config::load_config(path.unwrap_or(cli.config))?;
+            Ok(())
+        },
+        CliCommand::ConfigSchema => {
+            let schema = config::schema::config_schema();
+            println!("{}", serde_json::to_string_pretty(&schema)?);
+            Ok(())
+        },
+        CliCommand::Commands(action) => manage_commands(action, cli.config).await,
     }
+}
 
-    let token = std::env::var("DISCORD_TOKEN").context("get DISCORD_TOKEN env")?;
+/// Connects to the gateway and handles incoming events,
+/// with the config loaded from the given path.
+async fn run(cfg: Config, cfg_path: PathBuf) -> anyhow::Result<()> {
+    // Fail fast rather than when the first member verifies their rank.
+    let token = cfg.secrets.discord_token()?;
+    cfg.secrets.rover_api_key()?;
+
+    // Initialize the state.
+    let state = Context::new(token.clone(), cfg, cfg_path);
 
     // Use intents to only receive guild message events.
-    let shard = Shard::new(ShardId::ONE, token.clone(), Intents::empty());
+    let config = twilight_gateway::Config::new(token, Intents::empty());
+    let shards: Vec<Shard> = match &state.cfg().shards {
+        // Only run the configured range of shards, for multi-process deployments.
+        Some(range) => twilight_gateway::create_iterator(
+            range.start..range.end,
+            range.total,
+            config,
+            |_, builder| builder.build(),
+        )
+        .collect(),
+        // Otherwise run all the shards recommended by Discord.
+        None => {
+            twilight_gateway::create_recommended(&state.http, config, |_, builder| builder.build())
+                .await
+                .context("create recommended shards")?
+                .collect()
+        },
+    };
+    tracing::info!(shards = shards.len(), "starting shards");
 
-    // HTTP is separate from the gateway, so create a new client.
-    let http = Arc::new(HttpClient::new(token));
+    for shard in &shards {
+        state.health.register_shard(shard.id());
+    }
+    // Commands are only published by the first shard, so processes
+    // running a range without it don't wait for them.
+    if !shards.iter().any(|shard| shard.id().number() == 0) {
+        state.health.commands_published();
+    }
+
+    if let Some(address) = state.cfg().health_address {
+        let (health, config) = (state.health.clone(), state.config.clone());
+        let metrics = metrics::install()?;
+        tokio::spawn(async move {
+            if let Err(error) = health::serve(address, health, config, metrics).await {
+                tracing::error!(?error, "health server stopped");
+            }
+        });
+    }
 
     // Since we only care about new messages, make the cache only
     // cache new messages.
@@ -55,49 +224,219 @@ async fn main() -> anyhow::Result<()> {
         .resource_types(ResourceType::MESSAGE)
         .build();
 
-    // Parse the config file.
-    let cfg = Arc::new(config::load_config(config::config_path())?);
-    let req_client = Arc::new(reqwest::Client::new());
+    handle_event_wrapper(shards, cache, state).await?;
 
-    // Initialize the state.
-    let state = Context {
-        http: http.clone(),
-        cfg: cfg.clone(),
-        request: req_client.clone(),
-    };
+    // Flush any buffered log output before exiting.
+    std::io::stdout().flush()?;
+    Ok(())
+}
+
+/// Manages the application commands over HTTP, without connecting to the gateway.
+async fn manage_commands(action: CommandsAction, cfg_path: PathBuf) -> anyhow::Result<()> {
+    let cfg = config::load_config(&cfg_path)?;
+    if let CommandsAction::Dump = action {
+        // The command models don't require the HTTP client, so no token is needed.
+        let ctx = Context::new(String::new(), cfg, cfg_path);
+        let models = commands::models(ctx)?;
+        println!("{}", serde_json::to_string_pretty(&models)?);
+        return Ok(());
+    }
+
+    let ctx = Context::new(cfg.secrets.discord_token()?, cfg, cfg_path);
+    let application_id = metrics::discord(
+        "current_user_application",
+        ctx.http.current_user_application(),
+    )
+    .await
+    .context("fetch current application")?
+    .model()
+    .await
+    .context("get current application")?
+    .id;
+
+    match action {
+        CommandsAction::Publish => {
+            let diff = commands::sync::sync_global_commands(
+                &ctx.http,
+                application_id,
+                commands::models(ctx.clone())?,
+            )
+            .await
+            .context("sync global commands")?;
+
+            tracing::info!(
+                created = diff.create.len(),
+                updated = diff.update.len(),
+                deleted = diff.delete.len(),
+                unchanged = diff.unchanged,
+                "synced global commands"
+            );
+        },
+        CommandsAction::Clear => {
+            let interaction = ctx.http.interaction(application_id);
+            metrics::discord("set_global_commands", interaction.set_global_commands(&[]))
+                .await
+                .context("clear global commands")?;
+
+            tracing::info!("cleared global commands");
+        },
+        CommandsAction::Dump => unreachable!("handled above"),
+    }
 
-    handle_event_wrapper(shard, cache, state).await?;
     Ok(())
 }
 
 async fn handle_event_wrapper(
-    mut shard: Shard,
+    shards: Vec<Shard>,
     cache: DefaultInMemoryCache,
     state: Context,
 ) -> anyhow::Result<()> {
+    let closing = Arc::new(AtomicBool::new(false));
+    let senders: Vec<MessageSender> = shards.iter().map(Shard::sender).collect();
+
+    // Feed the events of every shard into the same dispatcher,
+    // the channel closes once all shards have stopped.
+    let (tx, mut rx) = mpsc::channel(EVENT_BUFFER);
+    for shard in shards {
+        tokio::spawn(receive_events(
+            shard,
+            tx.clone(),
+            closing.clone(),
+            state.health.clone(),
+        ));
+    }
+    drop(tx);
+
+    let mut tasks = JoinSet::new();
+    let mut shutdown = std::pin::pin!(shutdown_signal());
+    // Set when shutting down, the shards are given up on once it passes.
+    let mut deadline = None;
+    let mut timeout = std::pin::pin!(tokio::time::sleep(SHUTDOWN_TIMEOUT));
+
     // Process each event as they come in.
-    while let Some(item) = shard
-        .next_event(
-            // We only care about the `Ready` and `InteractionCreate` events.
-            EventTypeFlags::from_bits_retain(
-                EventTypeFlags::READY.bits() | EventTypeFlags::INTERACTION_CREATE.bits(),
-            ),
-        )
-        .await
-    {
-        let Ok(event) = item else {
-            tracing::warn!(source = ?item.unwrap_err(), "error receiving event");
-            continue;
+    loop {
+        let event = tokio::select! {
+            event = rx.recv() => event,
+            () = &mut shutdown, if !closing.load(Ordering::Relaxed) => {
+                tracing::info!("received shutdown signal, closing the shards");
+                closing.store(true, Ordering::Relaxed);
+                let at = Instant::now() + SHUTDOWN_TIMEOUT;
+                timeout.as_mut().reset(at);
+                deadline = Some(at);
+                for sender in &senders {
+                    // Fails if the shard already stopped.
+                    _ = sender.close(CloseFrame::NORMAL);
+                }
+                continue;
+            },
+            () = &mut timeout, if deadline.is_some() => {
+                tracing::warn!("timed out waiting for the shards to close");
+                break;
+            },
         };
 
+        let Some(event) = event else {
+            break;
+        };
+
+        // Stop accepting new events while the shards are closing.
+        if closing.load(Ordering::Relaxed) {
+            continue;
+        }
+
         // Update the cache with the event.
         cache.update(&event);
-        tokio::spawn(handle_event(event, state.clone()));
+        tasks.spawn(handle_event(event, state.clone()));
+
+        // Drop the finished tasks so the set doesn't grow indefinitely.
+        while tasks.try_join_next().is_some() {}
+    }
+
+    // Wait for the in-flight events to be handled,
+    // so role updates aren't interrupted mid-request.
+    tracing::info!(in_flight = tasks.len(), "waiting for in-flight events");
+    let drain = async { while tasks.join_next().await.is_some() {} };
+    let deadline = deadline.unwrap_or_else(|| Instant::now() + SHUTDOWN_TIMEOUT);
+    if tokio::time::timeout_at(deadline, drain).await.is_err() {
+        tracing::warn!(
+            aborted = tasks.len(),
+            "timed out waiting for in-flight events"
+        );
+        tasks.abort_all();
     }
 
     Ok(())
 }
 
+/// Receives the events of a shard and sends them to the dispatcher,
+/// until the shard is closed by the dispatcher.
+async fn receive_events(
+    mut shard: Shard,
+    tx: mpsc::Sender<Event>,
+    closing: Arc<AtomicBool>,
+    health: Arc<Health>,
+) {
+    let shard_id = shard.id();
+
+    // We only care about the `Ready` and `InteractionCreate` events,
+    // and `Resumed` to track the connection state.
+    let event_types = EventTypeFlags::from_bits_retain(
+        EventTypeFlags::READY.bits()
+            | EventTypeFlags::RESUMED.bits()
+            | EventTypeFlags::INTERACTION_CREATE.bits(),
+    );
+
+    while let Some(item) = shard.next_event(event_types).await {
+        let Ok(event) = item else {
+            tracing::warn!(shard = %shard_id, source = ?item.unwrap_err(), "error receiving event");
+            continue;
+        };
+
+        let connected = match event {
+            Event::Ready(_) | Event::Resumed => Some(true),
+            Event::GatewayClose(_) => Some(false),
+            _ => None,
+        };
+        health.record_event(shard_id, connected, shard.latency().average());
+        metrics::gateway_event(shard_id.number(), &format!("{:?}", event.kind()));
+
+        // The close is complete once the gateway acknowledges it.
+        if matches!(event, Event::GatewayClose(_)) && closing.load(Ordering::Relaxed) {
+            break;
+        }
+
+        if tx.send(event).await.is_err() {
+            break;
+        }
+    }
+
+    tracing::info!(shard = %shard_id, "shard stopped");
+}
+
+/// Resolves once the process receives SIGINT or SIGTERM.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {},
+                    _ = terminate.recv() => {},
+                }
+                return;
+            },
+            Err(error) => tracing::warn!(?error, "failed to listen for SIGTERM"),
+        }
+    }
+
+    if let Err(error) = tokio::signal::ctrl_c().await {
+        tracing::error!(?error, "failed to listen for SIGINT");
+        std::future::pending::<()>().await;
+    }
+}
+
 async fn handle_event(event: Event, ctx: Context) -> anyhow::Result<()> {
     let res: anyhow::Result<()> = match event {
         Event::Ready(client) => {
@@ -107,46 +446,49 @@ async fn handle_event(event: Event, ctx: Context) -> anyhow::Result<()> {
                 client.user.id
             );
 
-            // Publish commands every time the bot starts
-            // to ensure they are always up to date.
-            let global_commands = ctx
-                .http
-                .interaction(client.application.id)
-                .set_global_commands(commands::models(ctx.clone())?.as_slice())
-                .await
-                .context("publish global commands")?
-                .models()
-                .await
-                .context("get global commands")?;
+            // Commands are global, so only the first shard publishes them.
+            if client.shard.is_some_and(|id| id.number() != 0) {
+                return Ok(());
+            }
 
-            tracing::info!("published {} global commands", global_commands.len());
+            // Only publish the commands that changed since the last time the bot
+            // started, so reconnects don't churn the command set.
+            let diff = commands::sync::sync_global_commands(
+                &ctx.http,
+                client.application.id,
+                commands::models(ctx.clone())?,
+            )
+            .await
+            .context("sync global commands")?;
+
+            ctx.health.commands_published();
+            if diff.is_empty() {
+                tracing::info!(unchanged = diff.unchanged, "global commands are up to date");
+                return Ok(());
+            }
+
+            tracing::info!(
+                created = diff.create.len(),
+                updated = diff.update.len(),
+                deleted = diff.delete.len(),
+                unchanged = diff.unchanged,
+                "synced global commands"
+            );
             Ok(())
         },
         Event::InteractionCreate(interaction) => {
-            match &interaction.data {
-                Some(InteractionData::ApplicationCommand(command)) => {
-                    commands::handle_command(&interaction.0, command.name.as_str(), ctx.clone())
-                        .await
-                        .with_context(|| format!("handle command: {}", command.name))?;
-                },
-                Some(InteractionData::MessageComponent(component)) => {
-                    components::handle_component(
-                        &interaction.0,
-                        component.custom_id.as_str(),
-                        ctx.clone(),
-                    )
-                    .await
-                    .with_context(|| format!("handle component: {}", component.custom_id))?;
-                },
-                // Uncomment this when there is a modal to handle.
-                //
-                // Some(InteractionData::ModalSubmit(modal)) => {
-                // modals::handle_modal(&interaction.0, modal.custom_id.as_str(), state.clone())
-                //     .await
-                // },
-                _ => anyhow::bail!("unsupported interaction type"),
-            };
-            Ok(())
+            // Attach the interaction to every log line emitted while it's dispatched,
+            // the handler is recorded once it's resolved.
+            let span = tracing::info_span!(
+                "interaction",
+                id = %interaction.id,
+                guild = interaction.guild_id.map(tracing::field::display),
+                user = interaction.author_id().map(tracing::field::display),
+                handler = tracing::field::Empty,
+            );
+            handle_interaction(&interaction.0, ctx)
+                .instrument(span)
+                .await
         },
         _ => Ok(()),
     };
@@ -157,3 +499,45 @@ async fn handle_event(event: Event, ctx: Context) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Dispatches the interaction to its handler, responding to the user
+/// with an ephemeral error message if the handler fails.
+async fn handle_interaction(interaction: &Interaction, ctx: Context) -> anyhow::Result<()> {
+    let ctx = ctx.with_interaction(interaction);
+    let res = match (interaction.kind, &interaction.data) {
+        (
+            InteractionType::ApplicationCommand,
+            Some(InteractionData::ApplicationCommand(command)),
+        ) => commands::handle_command(interaction, command.name.as_str(), ctx.clone())
+            .await
+            .with_context(|| format!("handle command: {}", command.name)),
+        (InteractionType::MessageComponent, Some(InteractionData::MessageComponent(component))) => {
+            components::handle_component(interaction, component, ctx.clone())
+                .await
+                .with_context(|| format!("handle component: {}", component.custom_id))
+        },
+        // No command option is autocompleted, so there's nothing to suggest.
+        (InteractionType::ApplicationCommandAutocomplete, _) => {
+            return ctx.responder()?.autocomplete([]).await;
+        },
+        // Uncomment this when there is a modal to handle.
+        //
+        // (InteractionType::ModalSubmit, Some(InteractionData::ModalSubmit(modal))) => {
+        // modals::handle_modal(interaction, modal.custom_id.as_str(), state.clone())
+        //     .await
+        // },
+        (kind, _) => Err(anyhow::anyhow!(
+            "unsupported interaction type: {}",
+            kind.kind()
+        )),
+    };
+
+    let Err(err) = res else {
+        return Ok(());
+    };
+
+    InteractionError::from(err)
+        .respond(&ctx)
+        .await
+        .context("respond with error")
+}
@@ -0,0 +1,146 @@
+use std::future::IntoFuture;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use twilight_http::error::ErrorType;
+use twilight_http::response::Response;
+
+use crate::error::InteractionError;
+
+/// The buckets of the duration histograms, in seconds.
+const DURATION_BUCKETS: &[f64] = &[0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Installs the Prometheus recorder, returning the handle used to render the metrics.
+///
+/// Until the recorder is installed, the metrics are discarded.
+pub(crate) fn install() -> anyhow::Result<PrometheusHandle> {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Suffix("_duration_seconds".to_string()),
+            DURATION_BUCKETS,
+        )
+        .context("set duration buckets")?
+        .install_recorder()
+        .context("install prometheus recorder")
+}
+
+/// Records a handled interaction by its handler and outcome.
+pub(crate) fn interaction(handler: &str, result: &anyhow::Result<()>, elapsed: Duration) {
+    metrics::counter!(
+        "interactions_total",
+        "handler" => handler.to_string(),
+        "outcome" => outcome(result),
+    )
+    .increment(1);
+    metrics::histogram!(
+        "interaction_duration_seconds",
+        "handler" => handler.to_string(),
+    )
+    .record(elapsed);
+}
+
+/// Returns the outcome label of a handler result.
+fn outcome(result: &anyhow::Result<()>) -> &'static str {
+    match result {
+        Ok(()) => "ok",
+        Err(error) => match error.downcast_ref::<InteractionError>() {
+            Some(InteractionError::User(_)) => "user_error",
+            Some(InteractionError::Permission(_)) => "permission_denied",
+            _ => "error",
+        },
+    }
+}
+
+/// Records an event received from the gateway by its type.
+pub(crate) fn gateway_event(shard: u32, kind: &str) {
+    metrics::counter!(
+        "gateway_events_total",
+        "shard" => shard.to_string(),
+        "kind" => kind.to_string(),
+    )
+    .increment(1);
+}
+
+/// Sends a request to Discord, recording it by route and status.
+pub(crate) async fn discord<T>(
+    route: &'static str,
+    request: impl IntoFuture<Output = Result<Response<T>, twilight_http::Error>>,
+) -> Result<Response<T>, twilight_http::Error> {
+    let started = Instant::now();
+    let result = request.await;
+
+    let status = match &result {
+        Ok(response) => response.status().get().to_string(),
+        Err(error) => match error.kind() {
+            ErrorType::Response { status, .. } => status.get().to_string(),
+            _ => "error".to_string(),
+        },
+    };
+
+    metrics::counter!(
+        "discord_requests_total",
+        "route" => route,
+        "status" => status,
+    )
+    .increment(1);
+    metrics::histogram!("discord_request_duration_seconds", "route" => route)
+        .record(started.elapsed());
+
+    result
+}
+
+/// Sends a request to an external API, recording it by API and status.
+pub(crate) async fn external(
+    api: &'static str,
+    request: reqwest::RequestBuilder,
+) -> reqwest::Result<reqwest::Response> {
+    let started = Instant::now();
+    let result = request.send().await;
+
+    let status = match &result {
+        Ok(response) => response.status().as_u16().to_string(),
+        Err(_) => "error".to_string(),
+    };
+
+    metrics::counter!(
+        "external_requests_total",
+        "api" => api,
+        "status" => status,
+    )
+    .increment(1);
+    metrics::histogram!("external_request_duration_seconds", "api" => api)
+        .record(started.elapsed());
+
+    result
+}
+
+/// Records the outcome of a DevForum rank verification,
+/// with the trust level of the user if it was fetched.
+pub(crate) fn verification(outcome: &'static str, trust_level: Option<String>) {
+    metrics::counter!(
+        "verifications_total",
+        "outcome" => outcome,
+        "trust_level" => trust_level.unwrap_or_else(|| "unknown".to_string()),
+    )
+    .increment(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outcome_labels() {
+        let user =
+            anyhow::Error::from(InteractionError::user("invalid")).context("handle command: faq");
+
+        assert_eq!(outcome(&Ok(())), "ok");
+        assert_eq!(outcome(&Err(user)), "user_error");
+        assert_eq!(
+            outcome(&Err(InteractionError::permission("denied").into())),
+            "permission_denied"
+        );
+        assert_eq!(outcome(&Err(anyhow::anyhow!("request failed"))), "error");
+    }
+}
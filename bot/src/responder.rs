@@ -0,0 +1,409 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use tokio::sync::Mutex;
+use tracing::Instrument as _;
+use twilight_http::Client as HttpClient;
+use twilight_model::application::command::CommandOptionChoice;
+use twilight_model::application::interaction::Interaction;
+use twilight_model::channel::message::MessageFlags;
+use twilight_model::http::interaction::{
+    InteractionResponse, InteractionResponseData, InteractionResponseType,
+};
+use twilight_model::id::marker::{ApplicationMarker, InteractionMarker};
+use twilight_model::id::Id;
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+/// How long a handler can run before its interaction is deferred.
+/// Discord requires interactions to be acknowledged within 3 seconds.
+const AUTO_DEFER_AFTER: Duration = Duration::from_secs(2);
+
+/// How an interaction is deferred.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum Defer {
+    /// The response is visible to everyone in the channel.
+    #[default]
+    Public,
+    /// The response is only visible to the user.
+    Ephemeral,
+    /// The message the component is attached to is updated without a loading message,
+    /// only for component interactions.
+    Update,
+}
+
+/// The acknowledgement state of an interaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ResponseState {
+    /// The interaction hasn't been acknowledged yet.
+    Pending,
+    /// The interaction was acknowledged with the given deferral.
+    Deferred(Defer),
+    /// The interaction was responded to.
+    Responded,
+}
+
+/// Responds to an interaction, picking the right endpoint depending on whether
+/// the interaction was already acknowledged, and preventing it from being
+/// acknowledged twice.
+#[derive(Clone)]
+pub(crate) struct Responder {
+    http: Arc<HttpClient>,
+    application_id: Id<ApplicationMarker>,
+    interaction_id: Id<InteractionMarker>,
+    token: String,
+    state: Arc<Mutex<ResponseState>>,
+}
+
+impl Responder {
+    /// Create a new responder for the given interaction.
+    pub(crate) fn new(http: Arc<HttpClient>, interaction: &Interaction) -> Self {
+        Self {
+            http,
+            application_id: interaction.application_id,
+            interaction_id: interaction.id,
+            token: interaction.token.clone(),
+            state: Arc::new(Mutex::new(ResponseState::Pending)),
+        }
+    }
+
+    /// Returns the ID used to correlate the interaction with its log entries.
+    pub(crate) fn correlation_id(&self) -> String {
+        format!("{:x}", self.interaction_id.get())
+    }
+
+    /// Acknowledge the interaction with a loading message,
+    /// or without one for [component updates](Defer::Update).
+    ///
+    /// Does nothing if the interaction was already acknowledged.
+    pub(crate) async fn defer(&self, defer: Defer) -> anyhow::Result<()> {
+        let mut state = self.state.lock().await;
+        if *state != ResponseState::Pending {
+            return Ok(());
+        }
+
+        let (kind, flags) = match defer {
+            Defer::Public => (
+                InteractionResponseType::DeferredChannelMessageWithSource,
+                None,
+            ),
+            Defer::Ephemeral => (
+                InteractionResponseType::DeferredChannelMessageWithSource,
+                Some(MessageFlags::EPHEMERAL),
+            ),
+            Defer::Update => (InteractionResponseType::DeferredUpdateMessage, None),
+        };
+        self.create_response(kind, InteractionResponseData {
+            flags,
+            ..Default::default()
+        })
+        .await
+        .context("defer interaction response")?;
+
+        *state = ResponseState::Deferred(defer);
+        Ok(())
+    }
+
+    /// Respond to the interaction with a message.
+    ///
+    /// If the interaction was deferred, the loading message is replaced with the
+    /// response, in which case the message flags of the response are ignored.
+    /// An ephemeral response to a public loading message is sent as an ephemeral
+    /// followup instead, after deleting the loading message everyone can see.
+    /// If the interaction was already responded to, or deferred to
+    /// [update its message](Defer::Update), a followup message is sent instead.
+    pub(crate) async fn reply(&self, data: InteractionResponseData) -> anyhow::Result<()> {
+        let mut state = self.state.lock().await;
+        let ephemeral = data
+            .flags
+            .is_some_and(|flags| flags.contains(MessageFlags::EPHEMERAL));
+
+        match *state {
+            ResponseState::Pending => {
+                self.create_response(InteractionResponseType::ChannelMessageWithSource, data)
+                    .await
+                    .context("create interaction response")?;
+            },
+            ResponseState::Deferred(Defer::Public) if ephemeral => {
+                self.delete_response()
+                    .await
+                    .context("delete public deferred interaction response")?;
+                self.create_followup(data)
+                    .await
+                    .context("create ephemeral followup message")?;
+            },
+            ResponseState::Deferred(Defer::Public | Defer::Ephemeral) => {
+                self.update_response(data)
+                    .await
+                    .context("update deferred interaction response")?;
+            },
+            ResponseState::Deferred(Defer::Update) | ResponseState::Responded => {
+                self.create_followup(data)
+                    .await
+                    .context("create followup message")?;
+            },
+        }
+
+        *state = ResponseState::Responded;
+        Ok(())
+    }
+
+    /// Respond to the interaction with a message only visible to the user.
+    /// See [`Responder::reply`].
+    pub(crate) async fn reply_ephemeral(&self, content: impl Into<String>) -> anyhow::Result<()> {
+        self.reply(
+            InteractionResponseDataBuilder::new()
+                .content(content)
+                .flags(MessageFlags::EPHEMERAL)
+                .build(),
+        )
+        .await
+    }
+
+    /// Edit the original response, replacing the loading message if the interaction was deferred.
+    ///
+    /// Fails if the interaction wasn't acknowledged yet.
+    #[allow(dead_code)]
+    pub(crate) async fn edit(&self, data: InteractionResponseData) -> anyhow::Result<()> {
+        let mut state = self.state.lock().await;
+        if *state == ResponseState::Pending {
+            anyhow::bail!("cannot edit the response of an unacknowledged interaction");
+        }
+
+        self.update_response(data)
+            .await
+            .context("update interaction response")?;
+
+        *state = ResponseState::Responded;
+        Ok(())
+    }
+
+    /// Send a followup message, which replaces the
+    /// loading message if the interaction was deferred.
+    ///
+    /// Fails if the interaction wasn't acknowledged yet.
+    #[allow(dead_code)]
+    pub(crate) async fn followup(&self, data: InteractionResponseData) -> anyhow::Result<()> {
+        let mut state = self.state.lock().await;
+        if *state == ResponseState::Pending {
+            anyhow::bail!("cannot send a followup to an unacknowledged interaction");
+        }
+
+        self.create_followup(data)
+            .await
+            .context("create followup message")?;
+
+        *state = ResponseState::Responded;
+        Ok(())
+    }
+
+    /// Respond to a component interaction by updating the message the component is attached to.
+    ///
+    /// If the interaction was deferred, the original response is edited instead, which is
+    /// the message the component is attached to when [deferred to update it](Defer::Update).
+    /// Fails if the interaction was already responded to.
+    pub(crate) async fn update_message(&self, data: InteractionResponseData) -> anyhow::Result<()> {
+        let mut state = self.state.lock().await;
+        match *state {
+            ResponseState::Pending => {
+                self.create_response(InteractionResponseType::UpdateMessage, data)
+                    .await
+                    .context("update component message")?;
+            },
+            ResponseState::Deferred(_) => {
+                self.update_response(data)
+                    .await
+                    .context("update deferred component message")?;
+            },
+            ResponseState::Responded => anyhow::bail!("interaction was already responded to"),
+        }
+
+        *state = ResponseState::Responded;
+        Ok(())
+    }
+
+    /// Respond to the interaction with a modal.
+    ///
+    /// Fails if the interaction was already acknowledged.
+    #[allow(dead_code)]
+    pub(crate) async fn show_modal(&self, data: InteractionResponseData) -> anyhow::Result<()> {
+        self.acknowledge(InteractionResponseType::Modal, data)
+            .await
+            .context("show modal")
+    }
+
+    /// Respond to an autocomplete interaction with the given choices.
+    ///
+    /// Fails if the interaction was already acknowledged.
+    pub(crate) async fn autocomplete(
+        &self,
+        choices: impl IntoIterator<Item = CommandOptionChoice>,
+    ) -> anyhow::Result<()> {
+        self.acknowledge(
+            InteractionResponseType::ApplicationCommandAutocompleteResult,
+            InteractionResponseDataBuilder::new()
+                .choices(choices)
+                .build(),
+        )
+        .await
+        .context("respond with autocomplete choices")
+    }
+
+    /// Create the initial response, failing if the interaction was already acknowledged.
+    async fn acknowledge(
+        &self,
+        kind: InteractionResponseType,
+        data: InteractionResponseData,
+    ) -> anyhow::Result<()> {
+        let mut state = self.state.lock().await;
+        if *state != ResponseState::Pending {
+            anyhow::bail!("interaction was already acknowledged");
+        }
+
+        self.create_response(kind, data).await?;
+
+        *state = ResponseState::Responded;
+        Ok(())
+    }
+
+    async fn create_response(
+        &self,
+        kind: InteractionResponseType,
+        data: InteractionResponseData,
+    ) -> anyhow::Result<()> {
+        let request = self.http.interaction(self.application_id);
+        crate::metrics::discord(
+            "create_response",
+            request.create_response(self.interaction_id, &self.token, &InteractionResponse {
+                kind,
+                data: Some(data),
+            }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn update_response(&self, data: InteractionResponseData) -> anyhow::Result<()> {
+        let request = self.http.interaction(self.application_id);
+        crate::metrics::discord(
+            "update_response",
+            request
+                .update_response(&self.token)
+                .allowed_mentions(data.allowed_mentions.as_ref())
+                .attachments(data.attachments.as_deref().unwrap_or_default())
+                .components(data.components.as_deref())
+                .content(data.content.as_deref())
+                .embeds(data.embeds.as_deref()),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_response(&self) -> anyhow::Result<()> {
+        let request = self.http.interaction(self.application_id);
+        crate::metrics::discord("delete_response", request.delete_response(&self.token)).await?;
+        Ok(())
+    }
+
+    async fn create_followup(&self, data: InteractionResponseData) -> anyhow::Result<()> {
+        let request = self.http.interaction(self.application_id);
+        crate::metrics::discord(
+            "create_followup",
+            request
+                .create_followup(&self.token)
+                .allowed_mentions(data.allowed_mentions.as_ref())
+                .attachments(data.attachments.as_deref().unwrap_or_default())
+                .components(data.components.as_deref().unwrap_or_default())
+                .content(data.content.as_deref().unwrap_or_default())
+                .embeds(data.embeds.as_deref().unwrap_or_default())
+                .flags(data.flags.unwrap_or_else(MessageFlags::empty)),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// Runs the handler, deferring the interaction if the
+/// handler hasn't responded within [`AUTO_DEFER_AFTER`].
+pub(crate) async fn auto_defer<F>(responder: &Responder, defer: Defer, handler: F) -> F::Output
+where
+    F: Future,
+{
+    let mut handler = std::pin::pin!(handler);
+    tokio::select! {
+        output = &mut handler => return output,
+        () = tokio::time::sleep(AUTO_DEFER_AFTER) => {},
+    }
+
+    // Keep polling the handler while the interaction is being deferred,
+    // the responder ensures the handler's response is sent after the deferral.
+    let responder = responder.clone();
+    tokio::spawn(
+        async move {
+            if let Err(error) = responder.defer(defer).await {
+                tracing::warn!(?error, "failed to auto-defer interaction");
+            }
+        }
+        .in_current_span(),
+    );
+
+    handler.await
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::testing::{Simulation, APPLICATION_ID};
+
+    #[tokio::test]
+    async fn ephemeral_reply_to_public_deferral() {
+        let sim = Simulation::new().await;
+        let responder = Responder::new(sim.ctx.http.clone(), &sim.command("faq", json!([])));
+
+        responder.defer(Defer::Public).await.unwrap();
+        responder
+            .reply_ephemeral("Only you can see this")
+            .await
+            .unwrap();
+
+        // The public loading message is replaced by an ephemeral followup
+        let webhook = format!("/api/v10/webhooks/{APPLICATION_ID}/interaction-token");
+        let calls = sim.calls();
+        assert_eq!(calls[1].method, "DELETE");
+        assert_eq!(calls[1].path, format!("{webhook}/messages/@original"));
+        assert_eq!(calls[2].method, "POST");
+        assert_eq!(calls[2].path, webhook);
+        assert_eq!(
+            calls[2].body.as_ref().unwrap()["flags"],
+            MessageFlags::EPHEMERAL.bits()
+        );
+    }
+
+    #[tokio::test]
+    async fn update_message_after_deferral() {
+        let sim = Simulation::new().await;
+        let responder = Responder::new(sim.ctx.http.clone(), &sim.select("faq-browser", &[]));
+
+        responder.defer(Defer::Update).await.unwrap();
+        responder
+            .update_message(
+                InteractionResponseDataBuilder::new()
+                    .content("Answer")
+                    .build(),
+            )
+            .await
+            .unwrap();
+
+        let calls = sim.calls();
+        assert_eq!(
+            calls[0].body.as_ref().unwrap()["type"],
+            InteractionResponseType::DeferredUpdateMessage as u8
+        );
+        assert_eq!(calls[1].method, "PATCH");
+        assert!(calls[1].path.ends_with("/messages/@original"));
+        assert_eq!(calls[1].body.as_ref().unwrap()["content"], "Answer");
+    }
+}
@@ -0,0 +1,469 @@
+//! Simulates interactions end to end against a local stand-in server.
+//!
+//! The stand-in serves the Discord API and the [external APIs](crate::api::ExternalApi),
+//! recording every request made while handling the simulated interactions.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::StatusCode;
+use axum::middleware::{self, Next};
+use axum::response::Response;
+use axum::routing::get;
+use axum::Json;
+use serde_json::{json, Value};
+use twilight_model::application::interaction::Interaction;
+use twilight_model::guild::Permissions;
+
+use crate::api::{stand_in, HttpApi};
+use crate::config::live::LiveConfig;
+
+pub(crate) const APPLICATION_ID: u64 = 1;
+pub(crate) const GUILD_ID: u64 = 100;
+pub(crate) const CHANNEL_ID: u64 = 200;
+pub(crate) const USER_ID: u64 = 300;
+pub(crate) const INTERACTION_ID: u64 = 400;
+/// The roles of the guild, whose positions are their IDs minus 1000, such as 5 for `1005`.
+pub(crate) const ROLE_IDS: std::ops::RangeInclusive<u64> = 1001..=1010;
+
+/// A temporary directory for the files of a test, removed when dropped
+/// so failing tests don't leave files behind.
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Creates an empty directory, whose name must be unique among the tests.
+    pub(crate) fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("bot-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).expect("create temporary directory");
+        Self(path)
+    }
+
+    /// Returns the path of the file with the given name in the directory.
+    pub(crate) fn path(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+
+    /// Writes the file with the given name, creating its parent directories,
+    /// and returns its path.
+    pub(crate) fn write(&self, name: &str, contents: &str) -> PathBuf {
+        let path = self.path(name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("create temporary directory");
+        }
+        std::fs::write(&path, contents).expect("write temporary file");
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A request made to the stand-in server.
+#[derive(Debug, Clone)]
+pub(crate) struct Call {
+    pub(crate) method: String,
+    pub(crate) path: String,
+    /// The JSON body of the request, or the `payload_json` part of a multipart body.
+    pub(crate) body: Option<Value>,
+}
+
+/// The member that interacts, shared with the stand-in server.
+#[derive(Debug, Clone)]
+struct Member {
+    roles: Vec<u64>,
+    permissions: Permissions,
+}
+
+/// Runs simulated interactions through the dispatcher.
+pub(crate) struct Simulation {
+    pub(crate) ctx: crate::Context,
+    calls: Arc<Mutex<Vec<Call>>>,
+    member: Arc<Mutex<Member>>,
+    /// The locale of the member that interacts.
+    locale: String,
+}
+
+impl Simulation {
+    /// Starts the stand-in server and creates the state from the config fixture,
+    /// with a member without roles or permissions.
+    pub(crate) async fn new() -> Self {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let member = Arc::new(Mutex::new(Member {
+            roles: Vec::new(),
+            permissions: Permissions::empty(),
+        }));
+
+        let routes = stand_in::routes()
+            .route(
+                "/api/v10/guilds/{guild_id}/members/{user_id}",
+                get(guild_member)
+                    .patch(guild_member)
+                    .with_state(member.clone()),
+            )
+            .route("/api/v10/guilds/{guild_id}/roles", get(guild_roles))
+            // Every other Discord request succeeds without a body.
+            .fallback(|| async { StatusCode::NO_CONTENT })
+            .layer(middleware::from_fn_with_state(calls.clone(), record));
+        let urls = stand_in::serve(routes).await;
+
+        let cfg_path = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/config.yml"
+        ));
+        // The env of the process running the tests mustn't change the fixture.
+        let mut cfg =
+            crate::config::load_config_with(&cfg_path, |_| Ok(())).expect("load config fixture");
+        cfg.secrets.rover_api_key = Some("key".to_string().into());
+        let api = HttpApi::new(urls.clone(), &cfg.secrets);
+        let ctx = crate::Context::with_clients(
+            urls.discord_client("token".to_string()),
+            LiveConfig::new(cfg, cfg_path),
+            Arc::new(api),
+        );
+
+        Self {
+            ctx,
+            calls,
+            member,
+            locale: "en-US".to_string(),
+        }
+    }
+
+    /// Sets the roles and permissions of the member that interacts.
+    pub(crate) fn member(self, roles: &[u64], permissions: Permissions) -> Self {
+        *self.member.lock().unwrap() = Member {
+            roles: roles.to_vec(),
+            permissions,
+        };
+        self
+    }
+
+    /// Sets the locale of the member that interacts, the locale of the server stays `en-US`.
+    pub(crate) fn locale(mut self, locale: &str) -> Self {
+        self.locale = locale.to_string();
+        self
+    }
+
+    /// Builds a slash command interaction with the given options.
+    pub(crate) fn command(&self, name: &str, options: Value) -> Interaction {
+        self.interaction(
+            2,
+            json!({
+                "id": "10",
+                "name": name,
+                "type": 1,
+                "options": options,
+            }),
+        )
+    }
+
+    /// Builds an autocomplete interaction for the given command options.
+    pub(crate) fn autocomplete(&self, name: &str, options: Value) -> Interaction {
+        self.interaction(
+            4,
+            json!({
+                "id": "10",
+                "name": name,
+                "type": 1,
+                "options": options,
+            }),
+        )
+    }
+
+    /// Builds a button click interaction.
+    pub(crate) fn button(&self, custom_id: &str) -> Interaction {
+        self.interaction(
+            3,
+            json!({
+                "custom_id": custom_id,
+                "component_type": 2,
+                "values": [],
+            }),
+        )
+    }
+
+    /// Builds a text select menu interaction with the given chosen values.
+    pub(crate) fn select(&self, custom_id: &str, values: &[&str]) -> Interaction {
+        self.interaction(
+            3,
+            json!({
+                "custom_id": custom_id,
+                "component_type": 3,
+                "values": values,
+            }),
+        )
+    }
+
+    /// Builds a modal submit interaction with the given text input values.
+    pub(crate) fn modal(&self, custom_id: &str, fields: &[(&str, &str)]) -> Interaction {
+        let rows: Vec<Value> = fields
+            .iter()
+            .map(|(custom_id, value)| {
+                json!({
+                    "type": 1,
+                    "components": [{ "type": 4, "custom_id": custom_id, "value": value }],
+                })
+            })
+            .collect();
+        self.interaction(5, json!({ "custom_id": custom_id, "components": rows }))
+    }
+
+    /// Builds an interaction of the given type, used in the guild channel by the member.
+    fn interaction(&self, kind: u8, data: Value) -> Interaction {
+        let member = self.member.lock().unwrap().clone();
+        serde_json::from_value(json!({
+            "id": INTERACTION_ID.to_string(),
+            "application_id": APPLICATION_ID.to_string(),
+            "type": kind,
+            "data": data,
+            "guild_id": GUILD_ID.to_string(),
+            "channel": { "id": CHANNEL_ID.to_string(), "type": 0 },
+            "member": member_json(&member),
+            "token": "interaction-token",
+            "locale": self.locale,
+            "guild_locale": "en-US",
+            "authorizing_integration_owners": {},
+            "entitlements": [],
+        }))
+        .expect("deserialize simulated interaction")
+    }
+
+    /// Dispatches the interaction, waiting for its handler to finish.
+    ///
+    /// Handler errors are responded to, so only dispatch errors are returned.
+    pub(crate) async fn run(&self, interaction: &Interaction) -> anyhow::Result<()> {
+        crate::handle_interaction(interaction, self.ctx.clone()).await
+    }
+
+    /// Returns every request made to the stand-in server, in order.
+    pub(crate) fn calls(&self) -> Vec<Call> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Returns the bodies of the responses to the interaction,
+    /// including deferrals, edits and followups.
+    pub(crate) fn responses(&self) -> Vec<Value> {
+        self.calls()
+            .into_iter()
+            .filter(|call| call.path.contains("/interactions/") || call.path.contains("/webhooks/"))
+            .filter_map(|call| call.body)
+            .collect()
+    }
+
+    /// Returns the content of the last response to the interaction.
+    pub(crate) fn last_content(&self) -> Option<String> {
+        self.responses().iter().rev().find_map(|body| {
+            body.pointer("/data/content")
+                .or_else(|| body.get("content"))
+                .and_then(Value::as_str)
+                .map(ToString::to_string)
+        })
+    }
+}
+
+/// Records the request, then passes it on to the routes.
+async fn record(
+    State(calls): State<Arc<Mutex<Vec<Call>>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let (parts, body) = request.into_parts();
+    let bytes = axum::body::to_bytes(body, usize::MAX)
+        .await
+        .expect("read request body");
+
+    calls.lock().unwrap().push(Call {
+        method: parts.method.to_string(),
+        path: parts.uri.path().to_string(),
+        body: parse_body(&bytes),
+    });
+
+    next.run(Request::from_parts(parts, Body::from(bytes)))
+        .await
+}
+
+/// Parses a JSON body, or the `payload_json` part of a multipart body.
+fn parse_body(bytes: &[u8]) -> Option<Value> {
+    if let Ok(value) = serde_json::from_slice(bytes) {
+        return Some(value);
+    }
+
+    let body = String::from_utf8_lossy(bytes);
+    let (_, part) = body.split_once("name=\"payload_json\"")?;
+    let (_, payload) = part.split_once("\r\n\r\n")?;
+    let (payload, _) = payload.split_once("\r\n--")?;
+    serde_json::from_str(payload).ok()
+}
+
+fn member_json(member: &Member) -> Value {
+    json!({
+        "user": {
+            "id": USER_ID.to_string(),
+            "username": "user",
+            "discriminator": "0",
+            "avatar": null,
+        },
+        "roles": member.roles.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        "permissions": member.permissions.bits().to_string(),
+        "joined_at": "2020-01-01T00:00:00.000000+00:00",
+        "deaf": false,
+        "mute": false,
+        "flags": 0,
+    })
+}
+
+/// Responds with the member that interacts, also used as the response to member updates.
+async fn guild_member(State(member): State<Arc<Mutex<Member>>>) -> Json<Value> {
+    let member = member.lock().unwrap().clone();
+    Json(member_json(&member))
+}
+
+/// Responds with the [roles of the guild](ROLE_IDS).
+async fn guild_roles() -> Json<Value> {
+    let roles: Vec<Value> = ROLE_IDS
+        .map(|id| {
+            json!({
+                "id": id.to_string(),
+                "name": format!("role-{id}"),
+                "color": 0,
+                "hoist": false,
+                "managed": false,
+                "mentionable": false,
+                "permissions": "0",
+                "position": id - 1000,
+                "flags": 0,
+            })
+        })
+        .collect();
+    Json(Value::Array(roles))
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_model::application::command::{CommandOptionChoice, CommandOptionChoiceValue};
+    use twilight_model::application::interaction::InteractionType;
+    use twilight_model::http::interaction::InteractionResponseType;
+    use twilight_util::builder::InteractionResponseDataBuilder;
+
+    use super::*;
+    use crate::responder::Responder;
+
+    #[tokio::test]
+    async fn unhandled_interactions_are_answered() {
+        let sim = Simulation::new().await;
+
+        // Modals aren't handled yet, so the user is told something went wrong
+        sim.run(&sim.modal("feedback", &[("message", "hello")]))
+            .await
+            .unwrap();
+        assert!(sim
+            .last_content()
+            .unwrap()
+            .starts_with("Something went wrong while handling this interaction."));
+
+        // Autocompletion doesn't run the command
+        let sim = Simulation::new().await;
+        sim.run(&sim.autocomplete("faq", json!([]))).await.unwrap();
+        let responses = sim.responses();
+        assert_eq!(responses.len(), 1);
+        assert_eq!(
+            responses[0]["type"],
+            InteractionResponseType::ApplicationCommandAutocompleteResult as u8
+        );
+        assert_eq!(responses[0]["data"]["choices"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn modal_and_autocomplete_responses() {
+        let sim = Simulation::new().await;
+        let responder = Responder::new(sim.ctx.http.clone(), &sim.command("faq", json!([])));
+        responder
+            .show_modal(
+                InteractionResponseDataBuilder::new()
+                    .custom_id("feedback")
+                    .title("Feedback")
+                    .build(),
+            )
+            .await
+            .unwrap();
+        // A modal can only be the initial response
+        assert!(responder
+            .show_modal(InteractionResponseDataBuilder::new().build())
+            .await
+            .is_err());
+
+        let responder = Responder::new(sim.ctx.http.clone(), &sim.autocomplete("faq", json!([])));
+        responder
+            .autocomplete([CommandOptionChoice {
+                name: "Bugs".to_string(),
+                name_localizations: None,
+                value: CommandOptionChoiceValue::String("bugs".to_string()),
+            }])
+            .await
+            .unwrap();
+
+        let responses = sim.responses();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["type"], InteractionResponseType::Modal as u8);
+        assert_eq!(responses[0]["data"]["custom_id"], "feedback");
+        assert_eq!(
+            responses[1]["type"],
+            InteractionResponseType::ApplicationCommandAutocompleteResult as u8
+        );
+        assert_eq!(responses[1]["data"]["choices"][0]["value"], "bugs");
+    }
+
+    #[tokio::test]
+    async fn followup_after_response() {
+        let sim = Simulation::new().await;
+        let modal = sim.modal("feedback", &[("message", "hello")]);
+        let responder = Responder::new(sim.ctx.http.clone(), &modal);
+        let message = || {
+            InteractionResponseDataBuilder::new()
+                .content("Thanks")
+                .build()
+        };
+
+        assert!(responder.followup(message()).await.is_err());
+        responder.reply_ephemeral("Received").await.unwrap();
+        responder.followup(message()).await.unwrap();
+
+        let calls = sim.calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[1].method, "POST");
+        assert_eq!(
+            calls[1].path,
+            format!("/api/v10/webhooks/{APPLICATION_ID}/interaction-token")
+        );
+        assert_eq!(calls[1].body.as_ref().unwrap()["content"], "Thanks");
+    }
+
+    #[tokio::test]
+    async fn interaction_kinds() {
+        let sim = Simulation::new().await;
+
+        assert_eq!(
+            sim.command("faq", json!([])).kind,
+            InteractionType::ApplicationCommand
+        );
+        assert_eq!(
+            sim.autocomplete("faq", json!([])).kind,
+            InteractionType::ApplicationCommandAutocomplete
+        );
+        assert_eq!(sim.button("a").kind, InteractionType::MessageComponent);
+        assert_eq!(sim.modal("a", &[]).kind, InteractionType::ModalSubmit);
+    }
+
+    #[test]
+    fn multipart_payload() {
+        let body = "--boundary\r\nContent-Disposition: form-data; name=\"files[0]\"; filename=\"a.txt\"\r\n\r\nfile\r\n--boundary\r\nContent-Disposition: form-data; name=\"payload_json\"\r\n\r\n{\"type\":4}\r\n--boundary--\r\n";
+
+        assert_eq!(parse_body(body.as_bytes()), Some(json!({ "type": 4 })));
+    }
+}
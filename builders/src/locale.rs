@@ -1,8 +1,10 @@
+use std::convert::Infallible;
 use std::fmt::Display;
+use std::str::FromStr;
 
 /// Represents locales supported by the API.
 /// See the [API docs](https://discord.com/developers/docs/reference#locales) for more information.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Locale {
     Indonesian,
     Danish,
@@ -79,3 +81,65 @@ impl Display for Locale {
         write!(f, "{str}")
     }
 }
+
+/// Parses the locale from its code, any unsupported code is kept as [`Locale::Other`].
+impl FromStr for Locale {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "id" => Locale::Indonesian,
+            "da" => Locale::Danish,
+            "de" => Locale::German,
+            "en-GB" => Locale::EnglishUK,
+            "en-US" => Locale::EnglishUS,
+            "es-ES" => Locale::Spanish,
+            "es-419" => Locale::SpanishLATAM,
+            "fr" => Locale::French,
+            "hr" => Locale::Croatian,
+            "it" => Locale::Italian,
+            "lt" => Locale::Lithuanian,
+            "hu" => Locale::Hungarian,
+            "nl" => Locale::Dutch,
+            "no" => Locale::Norwegian,
+            "pl" => Locale::Polish,
+            "pt-BR" => Locale::PortugueseBrazilian,
+            "ro" => Locale::RomanianRomania,
+            "fi" => Locale::Finnish,
+            "sv-SE" => Locale::Swedish,
+            "vi" => Locale::Vietnamese,
+            "tr" => Locale::Turkish,
+            "cs" => Locale::Czech,
+            "el" => Locale::Greek,
+            "bg" => Locale::Bulgarian,
+            "ru" => Locale::Russian,
+            "uk" => Locale::Ukrainian,
+            "hi" => Locale::Hindi,
+            "th" => Locale::Thai,
+            "zh-CN" => Locale::ChineseChina,
+            "ja" => Locale::Japanese,
+            "zh-TW" => Locale::ChineseTaiwan,
+            "ko" => Locale::Korean,
+            other => Locale::Other(other.to_owned()),
+        })
+    }
+}
+
+impl From<Locale> for String {
+    fn from(locale: Locale) -> Self {
+        locale.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_codes() {
+        assert_eq!("fr".parse(), Ok(Locale::French));
+        assert_eq!("es-419".parse(), Ok(Locale::SpanishLATAM));
+        assert_eq!("tlh".parse(), Ok(Locale::Other("tlh".to_owned())));
+        assert_eq!(String::from(Locale::PortugueseBrazilian), "pt-BR");
+    }
+}